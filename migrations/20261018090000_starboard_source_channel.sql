@@ -0,0 +1 @@
+ALTER TABLE starboard_tracked ADD source_channel BIGINT;
@@ -0,0 +1,5 @@
+ALTER TABLE starboard ADD self_star BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE starboard ADD ignore_bots BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE starboard ADD count_starboard_reactions BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,18 @@
+CREATE TABLE starboard_channel_filter(
+  guild_id BIGINT,
+  emoji TEXT,
+  channel_id BIGINT,
+  allow BOOLEAN NOT NULL,
+
+  PRIMARY KEY(guild_id, emoji, channel_id),
+  CONSTRAINT fk_starboard FOREIGN KEY(guild_id, emoji) REFERENCES starboard(guild_id, emoji) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE TABLE starboard_role_filter(
+  guild_id BIGINT,
+  emoji TEXT,
+  role_id BIGINT,
+
+  PRIMARY KEY(guild_id, emoji, role_id),
+  CONSTRAINT fk_starboard FOREIGN KEY(guild_id, emoji) REFERENCES starboard(guild_id, emoji) ON DELETE CASCADE ON UPDATE CASCADE
+);
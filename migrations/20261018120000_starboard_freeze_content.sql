@@ -0,0 +1 @@
+ALTER TABLE starboard ADD freeze_content BOOLEAN NOT NULL DEFAULT FALSE;
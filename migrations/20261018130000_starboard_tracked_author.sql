@@ -0,0 +1 @@
+ALTER TABLE starboard_tracked ADD author_id BIGINT;
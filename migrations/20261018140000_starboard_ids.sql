@@ -0,0 +1,53 @@
+-- Starboards get their own id so several of them can share an emoji, as tiers with increasing thresholds
+ALTER TABLE starboard ADD id SERIAL;
+
+ALTER TABLE starboard ADD keep_promoted BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- Everything that pointed at a starboard by (guild_id, emoji) now points at its id
+ALTER TABLE starboard_channel_filter ADD starboard_id INTEGER;
+
+UPDATE starboard_channel_filter SET starboard_id = starboard.id FROM starboard
+WHERE starboard.guild_id = starboard_channel_filter.guild_id AND starboard.emoji = starboard_channel_filter.emoji;
+
+ALTER TABLE starboard_channel_filter DROP guild_id, DROP emoji;
+
+ALTER TABLE starboard_role_filter ADD starboard_id INTEGER;
+
+UPDATE starboard_role_filter SET starboard_id = starboard.id FROM starboard
+WHERE starboard.guild_id = starboard_role_filter.guild_id AND starboard.emoji = starboard_role_filter.emoji;
+
+ALTER TABLE starboard_role_filter DROP guild_id, DROP emoji;
+
+ALTER TABLE starboard_tracked ADD starboard_id INTEGER;
+
+UPDATE starboard_tracked SET starboard_id = starboard.id FROM starboard
+WHERE starboard.emoji = starboard_tracked.emoji AND starboard.starboard_channel = starboard_tracked.starboard_channel;
+
+-- Entries of starboards that no longer exist have nothing to be attached to
+DELETE FROM starboard_tracked WHERE starboard_id IS NULL;
+
+ALTER TABLE starboard_tracked DROP emoji, DROP starboard_channel;
+
+ALTER TABLE starboard DROP CONSTRAINT starboard_pkey;
+
+ALTER TABLE starboard ADD PRIMARY KEY(id);
+
+ALTER TABLE starboard ADD CONSTRAINT unique_tier UNIQUE(guild_id, emoji, min_reactions);
+
+ALTER TABLE starboard_channel_filter ADD PRIMARY KEY(starboard_id, channel_id),
+  ADD CONSTRAINT fk_starboard FOREIGN KEY(starboard_id) REFERENCES starboard(id) ON DELETE CASCADE;
+
+ALTER TABLE starboard_role_filter ADD PRIMARY KEY(starboard_id, role_id),
+  ADD CONSTRAINT fk_starboard FOREIGN KEY(starboard_id) REFERENCES starboard(id) ON DELETE CASCADE;
+
+ALTER TABLE starboard_tracked ADD PRIMARY KEY(message_id, starboard_id),
+  ADD CONSTRAINT fk_starboard FOREIGN KEY(starboard_id) REFERENCES starboard(id) ON DELETE CASCADE;
+
+-- A message promoted through several tiers of the same emoji counts once, at its highest tier
+CREATE VIEW starred_message AS
+SELECT DISTINCT ON (starboard_tracked.message_id, starboard.emoji)
+  starboard.guild_id, starboard.emoji, starboard.starboard_channel, starboard_tracked.message_id,
+  starboard_tracked.starboard_post_id, starboard_tracked.source_channel, starboard_tracked.author_id, starboard_tracked.reaction_count
+FROM starboard_tracked
+JOIN starboard ON starboard.id = starboard_tracked.starboard_id
+ORDER BY starboard_tracked.message_id, starboard.emoji, starboard.min_reactions DESC;
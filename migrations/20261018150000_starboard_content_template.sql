@@ -0,0 +1,11 @@
+ALTER TABLE starboard ADD content_template TEXT NOT NULL DEFAULT '{emoji} {count}';
+
+-- The emoji shown on posts can change as they collect more reactions, e.g. ⭐ then 🌟 then 💫
+CREATE TABLE starboard_emoji_tier(
+  starboard_id INTEGER,
+  min_count INTEGER,
+  emoji TEXT NOT NULL,
+
+  PRIMARY KEY(starboard_id, min_count),
+  CONSTRAINT fk_starboard FOREIGN KEY(starboard_id) REFERENCES starboard(id) ON DELETE CASCADE
+);
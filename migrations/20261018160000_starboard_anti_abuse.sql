@@ -0,0 +1,15 @@
+ALTER TABLE starboard ADD min_account_days INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE starboard ADD min_member_days INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE starboard ADD CONSTRAINT not_neg_min_ages CHECK (min_account_days >= 0 AND min_member_days >= 0);
+
+-- The users whose reactions got a message onto a starboard
+CREATE TABLE starboard_audit(
+  starboard_id INTEGER,
+  message_id BIGINT,
+  user_id BIGINT,
+
+  PRIMARY KEY(starboard_id, message_id, user_id),
+  CONSTRAINT fk_starboard FOREIGN KEY(starboard_id) REFERENCES starboard(id) ON DELETE CASCADE
+);
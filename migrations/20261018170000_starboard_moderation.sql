@@ -0,0 +1,18 @@
+-- Messages mods have locked in place on the starboards, or removed from them for good
+CREATE TABLE starboard_locked_message(
+  guild_id BIGINT,
+  message_id BIGINT,
+  removed BOOLEAN NOT NULL,
+
+  PRIMARY KEY(guild_id, message_id),
+  CONSTRAINT fk_guild FOREIGN KEY(guild_id) REFERENCES guild(discord_id)
+);
+
+-- Users whose messages can never be starred
+CREATE TABLE starboard_blacklist(
+  guild_id BIGINT,
+  user_id BIGINT,
+
+  PRIMARY KEY(guild_id, user_id),
+  CONSTRAINT fk_guild FOREIGN KEY(guild_id) REFERENCES guild(discord_id)
+);
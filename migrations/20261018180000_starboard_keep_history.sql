@@ -0,0 +1,14 @@
+-- Whether posts stay on the starboard after their original message is deleted
+ALTER TABLE starboard ADD keep_history BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Posts kept after their original message was deleted are still tracked, so they are never starred themselves
+ALTER TABLE starboard_tracked ADD source_deleted BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE OR REPLACE VIEW starred_message AS
+SELECT DISTINCT ON (starboard_tracked.message_id, starboard.emoji)
+  starboard.guild_id, starboard.emoji, starboard.starboard_channel, starboard_tracked.message_id,
+  starboard_tracked.starboard_post_id, starboard_tracked.source_channel, starboard_tracked.author_id, starboard_tracked.reaction_count,
+  starboard_tracked.source_deleted
+FROM starboard_tracked
+JOIN starboard ON starboard.id = starboard_tracked.starboard_id
+ORDER BY starboard_tracked.message_id, starboard.emoji, starboard.min_reactions DESC;
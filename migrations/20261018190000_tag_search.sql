@@ -0,0 +1,6 @@
+-- Trigrams let tags be found by names that are only close to theirs
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX tag_name_trigram ON tag USING GIN (command_name gin_trgm_ops);
+
+CREATE INDEX tag_description_search ON tag USING GIN (to_tsvector('english', tag_description));
@@ -0,0 +1,17 @@
+-- Other names a tag can be used by, which follow the tag when it is edited or removed
+CREATE TABLE tag_alias(
+  guild_id BIGINT,
+  alias TEXT,
+  command_name TEXT NOT NULL,
+
+  PRIMARY KEY(guild_id, alias),
+  CONSTRAINT fk_tag FOREIGN KEY(guild_id, command_name) REFERENCES tag(guild_id, command_name) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX tag_alias_trigram ON tag_alias USING GIN (alias gin_trgm_ops);
+
+-- Every name a tag can be used by, its own and its aliases
+CREATE VIEW tag_name AS
+SELECT guild_id, command_name AS name, command_name FROM tag
+UNION ALL
+SELECT guild_id, alias, command_name FROM tag_alias;
@@ -0,0 +1,14 @@
+-- Tags can be sent as plain text, as an embed or with a file, their description is the text or embed description
+CREATE TYPE tag_content AS ENUM ('text', 'embed', 'attachment');
+
+ALTER TABLE tag ADD content_type tag_content NOT NULL DEFAULT 'text';
+
+ALTER TABLE tag ADD embed_title TEXT;
+
+ALTER TABLE tag ADD embed_color INTEGER;
+
+ALTER TABLE tag ADD embed_image TEXT;
+
+ALTER TABLE tag ADD attachment_name TEXT;
+
+ALTER TABLE tag ADD attachment BYTEA;
@@ -0,0 +1,31 @@
+ALTER TABLE guild ADD member_tags BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Tags added by members without moderator permissions belong to them, and only they and moderators may change them
+ALTER TABLE tag ADD creator_id BIGINT;
+
+ALTER TABLE tag ADD last_editor_id BIGINT;
+
+ALTER TABLE tag ADD member_owned BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Every version a tag has had, so changes can be looked back on and undone
+CREATE TABLE tag_revision(
+  guild_id BIGINT,
+  command_name TEXT,
+  revision INTEGER,
+  tag_description TEXT NOT NULL,
+  content_type tag_content NOT NULL,
+  embed_title TEXT,
+  embed_color INTEGER,
+  embed_image TEXT,
+  attachment_name TEXT,
+  attachment BYTEA,
+  editor_id BIGINT,
+  edited_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY(guild_id, command_name, revision),
+  CONSTRAINT fk_tag FOREIGN KEY(guild_id, command_name) REFERENCES tag(guild_id, command_name) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- Existing tags start out with their current version, by an unknown editor
+INSERT INTO tag_revision (guild_id, command_name, revision, tag_description, content_type, embed_title, embed_color, embed_image, attachment_name, attachment)
+SELECT guild_id, command_name, 1, tag_description, content_type, embed_title, embed_color, embed_image, attachment_name, attachment FROM tag;
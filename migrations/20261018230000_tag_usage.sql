@@ -0,0 +1,12 @@
+-- Every time a tag is used, to find out which tags are worth keeping
+CREATE TABLE tag_usage(
+  guild_id BIGINT NOT NULL,
+  command_name TEXT NOT NULL,
+  user_id BIGINT NOT NULL,
+  channel_id BIGINT NOT NULL,
+  used_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  CONSTRAINT fk_tag FOREIGN KEY(guild_id, command_name) REFERENCES tag(guild_id, command_name) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX tag_usage_tag ON tag_usage(guild_id, command_name, used_at);
@@ -0,0 +1,4 @@
+-- Tags can be sorted into categories so the tag list can be filtered
+ALTER TABLE tag ADD COLUMN category TEXT;
+
+CREATE INDEX tag_category ON tag(guild_id, category);
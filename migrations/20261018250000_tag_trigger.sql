@@ -0,0 +1,27 @@
+-- Tags can be sent automatically when a message matches one of their triggers
+CREATE TYPE tag_trigger_kind AS ENUM ('keyword', 'glob', 'regex');
+
+CREATE TABLE tag_trigger(
+  id SERIAL PRIMARY KEY,
+  guild_id BIGINT NOT NULL,
+  command_name TEXT NOT NULL,
+  kind tag_trigger_kind NOT NULL,
+  pattern TEXT NOT NULL,
+  cooldown_seconds INTEGER NOT NULL DEFAULT 60,
+
+  CONSTRAINT fk_tag FOREIGN KEY(guild_id, command_name) REFERENCES tag(guild_id, command_name) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX tag_trigger_guild ON tag_trigger(guild_id);
+
+-- Triggers only reply in these channels, and in threads inside them
+CREATE TABLE tag_trigger_channel(
+  guild_id BIGINT NOT NULL,
+  channel_id BIGINT PRIMARY KEY
+);
+
+-- Members with any of these roles never set off triggers
+CREATE TABLE tag_trigger_ignored_role(
+  guild_id BIGINT NOT NULL,
+  role_id BIGINT PRIMARY KEY
+);
@@ -0,0 +1,23 @@
+-- Files are kept once per tag and referred to by its revisions, instead of every revision holding its own copy
+CREATE TABLE tag_attachment(
+  id SERIAL PRIMARY KEY,
+  guild_id BIGINT NOT NULL,
+  command_name TEXT NOT NULL,
+  attachment BYTEA NOT NULL,
+
+  CONSTRAINT fk_tag FOREIGN KEY(guild_id, command_name) REFERENCES tag(guild_id, command_name) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX tag_attachment_tag ON tag_attachment(guild_id, command_name);
+
+ALTER TABLE tag_revision ADD attachment_id INTEGER REFERENCES tag_attachment(id) ON DELETE SET NULL;
+
+INSERT INTO tag_attachment (guild_id, command_name, attachment)
+SELECT DISTINCT guild_id, command_name, attachment FROM tag_revision WHERE attachment IS NOT NULL;
+
+UPDATE tag_revision SET attachment_id = tag_attachment.id
+FROM tag_attachment
+WHERE tag_attachment.guild_id = tag_revision.guild_id AND tag_attachment.command_name = tag_revision.command_name
+  AND tag_attachment.attachment = tag_revision.attachment;
+
+ALTER TABLE tag_revision DROP COLUMN attachment;
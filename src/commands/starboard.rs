@@ -1,13 +1,245 @@
-use crate::database::starboard::{add_starboard_tables, delete_starboard_tables};
-use crate::serenity::{Channel, ReactionType};
-use crate::{Context, Error};
+use crate::database::starboard::transfer::{
+    export_starboards, import_starboards, parse_starboard_import, validate_starboard_import,
+};
+use crate::database::starboard::{
+    add_starboard_blacklist, add_starboard_channel_filter, add_starboard_emoji_tier,
+    add_starboard_role_filter, add_starboard_tables, delete_starboard, edit_starboard_tables,
+    get_emoji_tiers, get_random_starred, get_starboard_audit, get_starboard_blacklist,
+    get_starboard_filters, get_starboard_summaries, get_starboards, get_top_starred,
+    get_user_star_stats, is_on_any_starboard, lock_starboard_message, original_message_id,
+    post_link, remove_starboard_blacklist, remove_starboard_channel_filter,
+    remove_starboard_emoji_tier, remove_starboard_role_filter, unlock_starboard_message,
+    update_starboard_entry, Starboard, StarboardEdit, StarredMessage, DEFAULT_CONTENT_TEMPLATE,
+    MAX_TEMPLATE_LENGTH,
+};
+use crate::serenity::{
+    Attachment, AutocompleteChoice, Channel, ChannelId, CreateAttachment, CreateEmbed,
+    GuildChannel, GuildId, Member, Mentionable, Message, MessageId, ReactionType, Role, Timestamp,
+    User,
+};
+use crate::util::{truncate, EMBED_DESCRIPTION_LENGTH, EMBED_FIELD_LENGTH, SECONDS_PER_DAY};
+use crate::{Context, Error, EMBED_COLOR};
+use core::mem;
+use poise::serenity_prelude::futures::StreamExt;
+use poise::CreateReply;
+use std::fmt::Write;
+
+/// How many scanned messages to wait between progress updates during a rescan
+const RESCAN_PROGRESS_INTERVAL: usize = 500;
+
+/// How far back a rescan can be limited to, further back than Discord existed
+const MAX_RESCAN_DAYS: u32 = 36500;
+
+/// How many messages to show on the starboard leaderboard
+const LEADERBOARD_SIZE: i64 = 10;
+
+/// The largest export file accepted, in bytes
+const MAX_IMPORT_SIZE: u32 = 8 * 1024 * 1024;
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands(
+        "list",
+        "create",
+        "edit",
+        "policy",
+        "options",
+        "emojis",
+        "channels",
+        "roles",
+        "delete",
+        "rescan",
+        "export",
+        "import",
+        "audit",
+        "remove",
+        "lock",
+        "unlock",
+        "blacklist",
+        "top",
+        "user",
+        "random"
+    )
+)]
+pub async fn starboard(ctx: Context<'_>) -> Result<(), Error> {
+    list_starboards(ctx).await
+}
+
+/// Lists all of the current starboards in this server
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    list_starboards(ctx).await
+}
+
+async fn list_starboards(ctx: Context<'_>) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let starboards = get_starboard_summaries(ctx.data(), guild).await?;
+
+    if starboards.is_empty() {
+        ctx.say("This server has no starboards!").await?;
+        return Ok(());
+    }
+
+    let total = starboards.len();
+    let mut pages = Vec::with_capacity(total);
+
+    for (i, starboard) in starboards.into_iter().enumerate() {
+        let channel = starboard.starboard_channel.into_serenity();
+
+        let mut lines = vec![
+            format!("Channel: {}", channel.mention()),
+            format!("Emoji: {}", starboard.emoji),
+            format!("Minimum reactions: {}", starboard.min_reactions),
+            format!("Self stars count: {}", yes_no(starboard.self_star)),
+            format!("Bot reactions count: {}", yes_no(!starboard.ignore_bots)),
+            format!(
+                "Starboard post reactions count: {}",
+                yes_no(starboard.count_starboard_reactions)
+            ),
+            format!("Posts follow edits: {}", yes_no(!starboard.freeze_content)),
+            format!(
+                "Posts kept after promotion: {}",
+                yes_no(starboard.keep_promoted)
+            ),
+            format!(
+                "Posts kept after the message is deleted: {}",
+                yes_no(starboard.keep_history)
+            ),
+            format!("Post template: `{}`", starboard.content_template),
+            format!("Tracked posts: {}", starboard.tracked_posts),
+        ];
+
+        let filters = get_starboard_filters(ctx.data(), starboard.id).await?;
+
+        if !filters.allowed_channels.is_empty() {
+            lines.push(format!(
+                "Only from: {}",
+                truncated_mention_list(&filters.allowed_channels, EMBED_FIELD_LENGTH)
+            ));
+        }
+
+        if !filters.denied_channels.is_empty() {
+            lines.push(format!(
+                "Never from: {}",
+                truncated_mention_list(&filters.denied_channels, EMBED_FIELD_LENGTH)
+            ));
+        }
+
+        if !filters.required_roles.is_empty() {
+            lines.push(format!(
+                "Reactions need one of: {}",
+                truncated_mention_list(&filters.required_roles, EMBED_FIELD_LENGTH)
+            ));
+        }
+
+        if starboard.min_account_days > 0 {
+            lines.push(format!(
+                "Accounts need to be {} days old",
+                starboard.min_account_days
+            ));
+        }
+
+        if starboard.min_member_days > 0 {
+            lines.push(format!(
+                "Members need to be in the server for {} days",
+                starboard.min_member_days
+            ));
+        }
+
+        let tiers = get_emoji_tiers(ctx.data(), starboard.id).await?;
+
+        if !tiers.is_empty() {
+            let tiers = tiers
+                .iter()
+                .map(|tier| format!("{} from {}", tier.emoji, tier.min_count))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            lines.push(format!(
+                "Emoji tiers: {}",
+                truncate(&tiers, EMBED_FIELD_LENGTH)
+            ));
+        }
+
+        if let (Some(post), Some(reactions)) = (starboard.top_post, starboard.top_reactions) {
+            let link =
+                post_link(ctx.serenity_context(), guild, channel, post.into_serenity()).await;
+            lines.push(format!(
+                "Top post: {link} ({} {reactions})",
+                starboard.emoji
+            ));
+        }
+
+        let heading = format!("**Starboard {} of {total}** (ID {})", i + 1, starboard.id);
+        let mut page = heading.clone();
+
+        // A starboard with long filter lists continues on the next page instead of going past the embed limit
+        for line in lines {
+            if page.chars().count() + 1 + line.chars().count() > EMBED_DESCRIPTION_LENGTH {
+                pages.push(mem::replace(&mut page, format!("{heading}, continued")));
+            }
+
+            page.push('\n');
+            page.push_str(&line);
+        }
+
+        pages.push(page);
+    }
+
+    let pages: Vec<&str> = pages.iter().map(String::as_str).collect();
+
+    poise::builtins::paginate(ctx, &pages).await?;
 
-//TODO List all of the current starboards under `starboard` and `starboard list`
-#[poise::command(slash_command, subcommands("create", "delete"))]
-pub async fn starboard(_ctx: Context<'_>, _arg: String) -> Result<(), Error> {
     Ok(())
 }
 
+/// Suggests the server's starboards by emoji, threshold and channel
+async fn autocomplete_starboard(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    let Some(guild) = ctx.guild_id() else {
+        return Vec::new();
+    };
+
+    let Ok(starboards) = get_starboards(ctx.data(), guild).await else {
+        return Vec::new();
+    };
+
+    let partial = partial.to_lowercase();
+
+    starboards
+        .into_iter()
+        .map(|starboard| (starboard_label(ctx, &starboard), starboard.id))
+        .filter(|(label, _)| label.to_lowercase().contains(&partial))
+        .map(|(label, id)| AutocompleteChoice::new(label, id))
+        .collect()
+}
+
+fn starboard_label(ctx: Context<'_>, starboard: &Starboard) -> String {
+    format!(
+        "{} {}+ in #{} (ID {})",
+        starboard.emoji,
+        starboard.min_reactions,
+        starboard_channel_name(ctx, starboard.starboard_channel.into_serenity()),
+        starboard.id
+    )
+}
+
+/// The name of a channel in plain text, for places where mentions do not work
+fn starboard_channel_name(ctx: Context<'_>, channel: ChannelId) -> String {
+    ctx.guild()
+        .and_then(|guild| guild.channels.get(&channel).map(|c| c.name.clone()))
+        .unwrap_or_else(|| channel.to_string())
+}
+
+fn yes_no(value: bool) -> &'static str {
+    match value {
+        true => "Yes",
+        false => "No",
+    }
+}
+
 /// Creates a new starboard with a optional custom emoji
 #[poise::command(
     slash_command,
@@ -17,9 +249,15 @@ pub async fn starboard(_ctx: Context<'_>, _arg: String) -> Result<(), Error> {
 )]
 pub async fn create(
     ctx: Context<'_>,
-    #[description = "The channel to put starboard in"] starboard: Option<Channel>,
+    #[description = "The channel to put starboard in, forum channels get a post for every message"]
+    #[channel_types("Text", "News", "Forum")]
+    starboard: Option<Channel>,
     #[description = "A custom emoji instead of a star"] emoji: Option<ReactionType>,
     #[description = "How many reactions you need to get onto starboard"] min_reactions: Option<i32>,
+    #[description = "Whether the author's own reaction counts"] self_star: Option<bool>,
+    #[description = "Whether reactions from bots are ignored"] ignore_bots: Option<bool>,
+    #[description = "Whether reactions on the starboard post also count"]
+    count_starboard_reactions: Option<bool>,
 ) -> Result<(), Error> {
     // SAFETY: Since this command is guild_only this should NEVER fail
     let guild = ctx.guild_id().unwrap();
@@ -40,33 +278,1090 @@ pub async fn create(
         .map(|x| x.to_string())
         .unwrap_or_else(|| "⭐".to_string());
 
-    add_starboard_tables(ctx.data(), guild, starboard, emoji.as_str(), min_reactions).await?;
+    let taken = get_starboards(ctx.data(), guild)
+        .await?
+        .into_iter()
+        .any(|starboard| starboard.emoji == emoji && starboard.min_reactions == min_reactions);
+
+    if taken {
+        ctx.say("There is already a starboard with that emoji and minimum reactions!")
+            .await?;
+        return Ok(());
+    }
+
+    let starboard = Starboard {
+        // Picked by the database
+        id: 0,
+        guild_id: guild.into(),
+        emoji,
+        starboard_channel: starboard.into(),
+        min_reactions,
+        self_star: self_star.unwrap_or(false),
+        ignore_bots: ignore_bots.unwrap_or(false),
+        count_starboard_reactions: count_starboard_reactions.unwrap_or(false),
+        freeze_content: false,
+        keep_promoted: true,
+        content_template: DEFAULT_CONTENT_TEMPLATE.to_string(),
+        min_account_days: 0,
+        min_member_days: 0,
+        keep_history: false,
+    };
+
+    add_starboard_tables(ctx.data(), &starboard).await?;
 
     ctx.say("Done!").await?;
 
     Ok(())
 }
 
-/// Delete the starboard in the channel, if it exists
+/// Changes the channel, emoji or minimum reactions of an existing starboard
 #[poise::command(
     slash_command,
     prefix_command,
     guild_only,
     required_permissions = "MANAGE_CHANNELS"
 )]
-pub async fn delete(
+pub async fn edit(
     ctx: Context<'_>,
-    #[description = "The channel to remove starboard from"] starboard: Option<Channel>,
+    #[description = "The starboard to edit"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "The channel to move starboard to"]
+    #[channel_types("Text", "News", "Forum")]
+    starboard: Option<Channel>,
+    #[description = "A new emoji for the starboard"] new_emoji: Option<ReactionType>,
+    #[description = "How many reactions you need to get onto starboard"] min_reactions: Option<i32>,
 ) -> Result<(), Error> {
-    let data = ctx.data();
-    let starboard = starboard
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    if min_reactions.is_some_and(|min| min <= 0) {
+        ctx.say("Minimum reactions should be not zero or negative!")
+            .await?;
+        return Ok(());
+    }
+
+    let starboards = get_starboards(ctx.data(), guild).await?;
+
+    let Some(current) = starboards.iter().find(|starboard| starboard.id == id) else {
+        ctx.say("There is no starboard with that ID!").await?;
+        return Ok(());
+    };
+
+    let new_emoji = new_emoji.map(|x| x.to_string());
+    let emoji = new_emoji.as_deref().unwrap_or(&current.emoji);
+    let threshold = min_reactions.unwrap_or(current.min_reactions);
+
+    let taken = starboards.iter().any(|starboard| {
+        starboard.id != id && starboard.emoji == emoji && starboard.min_reactions == threshold
+    });
+
+    if taken {
+        ctx.say("There is already a starboard with that emoji and minimum reactions!")
+            .await?;
+        return Ok(());
+    }
+
+    // Moving posts between channels can take a while
+    ctx.defer().await?;
+
+    let found = edit_starboard_tables(
+        ctx.serenity_context(),
+        ctx.data(),
+        guild,
+        id,
+        StarboardEdit {
+            channel: starboard.as_ref().map(Channel::id),
+            emoji: new_emoji.as_deref(),
+            min_reactions,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    if found {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("There is no starboard with that ID!").await?;
+    }
+
+    Ok(())
+}
+
+/// Changes which reactions count towards an existing starboard, applies from the next reaction
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn policy(
+    ctx: Context<'_>,
+    #[description = "The starboard to change"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "Whether the author's own reaction counts"] self_star: Option<bool>,
+    #[description = "Whether reactions from bots are ignored"] ignore_bots: Option<bool>,
+    #[description = "Whether reactions on the starboard post also count"]
+    count_starboard_reactions: Option<bool>,
+    #[description = "How many days old an account has to be for its reactions to count"]
+    min_account_days: Option<u16>,
+    #[description = "How many days someone has to be in the server for their reactions to count"]
+    min_member_days: Option<u16>,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let found = edit_starboard_tables(
+        ctx.serenity_context(),
+        ctx.data(),
+        guild,
+        id,
+        StarboardEdit {
+            self_star,
+            ignore_bots,
+            count_starboard_reactions,
+            min_account_days: min_account_days.map(i32::from),
+            min_member_days: min_member_days.map(i32::from),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    if found {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("There is no starboard with that ID!").await?;
+    }
+
+    Ok(())
+}
+
+/// Changes how the posts of an existing starboard behave
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn options(
+    ctx: Context<'_>,
+    #[description = "The starboard to change"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "Keep posts as the message was when starred instead of following edits"]
+    freeze_content: Option<bool>,
+    #[description = "Keep posts here when they get promoted to a higher tier of the same emoji"]
+    keep_promoted: Option<bool>,
+    #[description = "Keep posts here when the original message or its channel gets deleted"]
+    keep_history: Option<bool>,
+    #[description = "The text of posts, {emoji}, {count} and {channel} get filled in"]
+    template: Option<String>,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    if template
         .as_ref()
-        .map(Channel::id)
-        .unwrap_or(ctx.channel_id());
+        .is_some_and(|template| template.chars().count() > MAX_TEMPLATE_LENGTH)
+    {
+        ctx.say(format!(
+            "The template can be at most {MAX_TEMPLATE_LENGTH} characters long!"
+        ))
+        .await?;
+        return Ok(());
+    }
 
-    delete_starboard_tables(data, starboard).await?;
+    // Posts get edited to match a new template, which can take a while
+    if template.is_some() {
+        ctx.defer().await?;
+    }
 
-    ctx.say("Done!").await?;
+    let found = edit_starboard_tables(
+        ctx.serenity_context(),
+        ctx.data(),
+        guild,
+        id,
+        StarboardEdit {
+            freeze_content,
+            keep_promoted,
+            keep_history,
+            content_template: template.as_deref(),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    if found {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("There is no starboard with that ID!").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands("add_emoji_tier", "remove_emoji_tier")
+)]
+pub async fn emojis(_ctx: Context<'_>, _arg: String) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Shows a different emoji on posts once they reach a number of reactions
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "add",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn add_emoji_tier(
+    ctx: Context<'_>,
+    #[description = "The starboard"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "How many reactions a post needs to show this emoji"] min_count: i32,
+    #[description = "The emoji to show"] emoji: ReactionType,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    if min_count <= 0 {
+        ctx.say("Reactions should be not zero or negative!").await?;
+        return Ok(());
+    }
+
+    let found =
+        add_starboard_emoji_tier(ctx.data(), guild, id, min_count, emoji.to_string().as_str())
+            .await?;
+
+    if found {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("There is no starboard with that ID!").await?;
+    }
+
+    Ok(())
+}
+
+/// Stops showing a different emoji from a number of reactions
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "remove",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn remove_emoji_tier(
+    ctx: Context<'_>,
+    #[description = "The starboard"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "The number of reactions the emoji is shown from"] min_count: i32,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let removed = remove_starboard_emoji_tier(ctx.data(), guild, id, min_count).await?;
+
+    if removed {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("That starboard has no emoji tier from that many reactions!")
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands("allow_channel", "deny_channel", "remove_channel")
+)]
+pub async fn channels(_ctx: Context<'_>, _arg: String) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Only lets messages from allowed channels onto the starboard
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "allow",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn allow_channel(
+    ctx: Context<'_>,
+    #[description = "The starboard"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "The channel or category to allow"] channel: Channel,
+) -> Result<(), Error> {
+    filter_channel(ctx, id, channel, true).await
+}
+
+/// Keeps messages from a channel off the starboard
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "deny",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn deny_channel(
+    ctx: Context<'_>,
+    #[description = "The starboard"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "The channel or category to deny"] channel: Channel,
+) -> Result<(), Error> {
+    filter_channel(ctx, id, channel, false).await
+}
+
+async fn filter_channel(
+    ctx: Context<'_>,
+    id: i32,
+    channel: Channel,
+    allow: bool,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let found = add_starboard_channel_filter(ctx.data(), guild, id, channel.id(), allow).await?;
+
+    if found {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("There is no starboard with that ID!").await?;
+    }
+
+    Ok(())
+}
+
+/// Removes a channel from the allowed and denied channels of a starboard
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "remove",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn remove_channel(
+    ctx: Context<'_>,
+    #[description = "The starboard"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "The channel or category to remove"] channel: Channel,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let removed = remove_starboard_channel_filter(ctx.data(), guild, id, channel.id()).await?;
+
+    if removed {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("That channel is not allowed or denied on that starboard!")
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands("add_required_role", "remove_required_role")
+)]
+pub async fn roles(_ctx: Context<'_>, _arg: String) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Only counts reactions from members with one of the required roles
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "add",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn add_required_role(
+    ctx: Context<'_>,
+    #[description = "The starboard"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "The role to require"] role: Role,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let found = add_starboard_role_filter(ctx.data(), guild, id, role.id).await?;
+
+    if found {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("There is no starboard with that ID, or the role is already required!")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Stops requiring a role for reactions to count
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "remove",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn remove_required_role(
+    ctx: Context<'_>,
+    #[description = "The starboard"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+    #[description = "The role to stop requiring"] role: Role,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let removed = remove_starboard_role_filter(ctx.data(), guild, id, role.id).await?;
+
+    if removed {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("That role is not required on that starboard!")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Delete a starboard, if it exists
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn delete(
+    ctx: Context<'_>,
+    #[description = "The starboard to delete"]
+    #[autocomplete = "autocomplete_starboard"]
+    id: i32,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    if delete_starboard(ctx.data(), guild, id).await? {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("There is no starboard with that ID!").await?;
+    }
+
+    Ok(())
+}
+
+/// Scans a channel's history for messages that should be on a starboard
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn rescan(
+    ctx: Context<'_>,
+    #[description = "The channel to scan"] channel: GuildChannel,
+    #[description = "Only scan messages from the last this many days"] since: Option<u32>,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    if channel.guild_id != guild {
+        ctx.say("That channel is not in this server!").await?;
+        return Ok(());
+    }
+
+    if since.is_some_and(|days| days > MAX_RESCAN_DAYS) {
+        ctx.say(format!(
+            "You can scan at most the last {MAX_RESCAN_DAYS} days, leave it out to scan everything!"
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let starboards = get_starboards(ctx.data(), guild).await?;
+
+    if starboards.is_empty() {
+        ctx.say("This server has no starboards!").await?;
+        return Ok(());
+    }
+
+    // Starboards come sorted by emoji and then threshold, so each chunk holds the tiers of one emoji
+    let tiers: Vec<&[Starboard]> = starboards.chunk_by(|a, b| a.emoji == b.emoji).collect();
+
+    let cutoff = match since {
+        Some(days) => Some(Timestamp::from_unix_timestamp(
+            Timestamp::now().unix_timestamp() - i64::from(days) * SECONDS_PER_DAY,
+        )?),
+        None => None,
+    };
+
+    let progress = ctx.say("Scanning...").await?;
+
+    // Serenity waits out rate limits for us, so we only need to avoid spending requests where we can
+    let mut history = channel.id.messages_iter(ctx).boxed();
+    let mut scanned = 0;
+    let mut starred = 0;
+
+    while let Some(message) = history.next().await.transpose()? {
+        if cutoff.is_some_and(|cutoff| message.timestamp < cutoff) {
+            break;
+        }
+
+        scanned += 1;
+
+        for tiers in &tiers {
+            let reaction = message
+                .reactions
+                .iter()
+                .find(|r| r.reaction_type.to_string() == tiers[0].emoji);
+
+            // Messages without enough reactions for even the lowest tier only need a recount
+            // if they are on a starboard, which takes them off unless their entries are locked
+            if reaction.map_or(0, |reaction| reaction.count) < tiers[0].min_reactions as u64
+                && !is_on_any_starboard(ctx.data(), message.id, tiers).await?
+            {
+                continue;
+            }
+
+            let emoji = match reaction {
+                Some(reaction) => reaction.reaction_type.clone(),
+                None => ReactionType::try_from(tiers[0].emoji.as_str())?,
+            };
+
+            let on_starboard = update_starboard_entry(
+                ctx.serenity_context(),
+                ctx.data(),
+                message.clone(),
+                emoji,
+                tiers,
+            )
+            .await?;
+
+            if on_starboard {
+                starred += 1;
+            }
+        }
+
+        if scanned % RESCAN_PROGRESS_INTERVAL == 0 {
+            progress
+                .edit(
+                    ctx,
+                    CreateReply::default().content(format!(
+                        "Scanning... {scanned} messages checked, {starred} on starboard so far"
+                    )),
+                )
+                .await?;
+        }
+    }
+
+    progress
+        .edit(
+            ctx,
+            CreateReply::default().content(format!(
+                "Done! Checked {scanned} messages, {starred} are on starboard"
+            )),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Exports this server's starboards and their posts as a JSON file
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn export(ctx: Context<'_>) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let export = export_starboards(ctx.data(), guild).await?;
+    let file = serde_json::to_vec_pretty(&export)?;
+
+    ctx.send(CreateReply::default().attachment(CreateAttachment::bytes(file, "starboards.json")))
+        .await?;
+
+    Ok(())
+}
+
+/// Recreates starboards from an export of this bot or another starboard bot
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn import(
+    ctx: Context<'_>,
+    #[description = "The exported JSON file"] file: Attachment,
+    #[description = "Where to put starboards whose channel is not in this server"]
+    #[channel_types("Text", "News", "Forum")]
+    channel: Option<Channel>,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    if file.size > MAX_IMPORT_SIZE {
+        ctx.say("That file is too big to import!").await?;
+        return Ok(());
+    }
+
+    let Some(import) = parse_starboard_import(&file.download().await?) else {
+        ctx.say("That file is not a starboard export I can read!")
+            .await?;
+        return Ok(());
+    };
+
+    if let Err(problem) = validate_starboard_import(ctx.serenity_context(), guild, &import).await {
+        ctx.say(format!("That export can not be imported: {problem}"))
+            .await?;
+        return Ok(());
+    }
+
+    // Posts may have to be sent again, which can take a while
+    ctx.defer().await?;
+
+    let summary = import_starboards(
+        ctx.serenity_context(),
+        ctx.data(),
+        guild,
+        import,
+        channel.as_ref().map(Channel::id),
+    )
+    .await?;
+
+    let mut reply = format!(
+        "Done! Imported {} starboards: {} posts linked again, {} posted again and {} could not be found",
+        summary.starboards, summary.relinked, summary.reposted, summary.lost
+    );
+
+    if summary.declined > 0 {
+        write!(
+            &mut reply,
+            "\n{} posts were left out as their message is locked, removed, blacklisted or no longer has enough reactions",
+            summary.declined
+        )?;
+    }
+
+    if summary.skipped_starboards > 0 {
+        write!(
+            &mut reply,
+            "\n{} starboards were skipped as their channel is not in this server, pick a channel to import them into",
+            summary.skipped_starboards
+        )?;
+    }
+
+    ctx.say(reply).await?;
+
+    Ok(())
+}
+
+/// Shows whose reactions got a message onto the starboards
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn audit(
+    ctx: Context<'_>,
+    #[description = "A link to the starred message"] message: Message,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let audits = get_starboard_audit(ctx.data(), guild, message.id).await?;
+
+    if audits.is_empty() {
+        ctx.say("There is no record of who got that message onto a starboard!")
+            .await?;
+        return Ok(());
+    }
+
+    let mut embed = CreateEmbed::new()
+        .title("Who starred this message")
+        .url(message.id.link(message.channel_id, Some(guild)))
+        .color(EMBED_COLOR);
+
+    // Discord only allows this many embed fields
+    for audit in audits.into_iter().take(25) {
+        let users: Vec<_> = audit
+            .users
+            .into_iter()
+            .map(|id| id.into_serenity())
+            .collect();
+
+        embed = embed.field(
+            format!(
+                "{} in #{}",
+                audit.emoji,
+                starboard_channel_name(ctx, audit.starboard_channel.into_serenity())
+            ),
+            truncated_mention_list(&users, EMBED_FIELD_LENGTH),
+            false,
+        );
+    }
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Takes a message off the starboards for good
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "A link to the message or its starboard post"] message: Message,
+) -> Result<(), Error> {
+    moderate_message(ctx, message, true).await
+}
+
+/// Locks the starboard posts of a message so they stop following its reactions and edits
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn lock(
+    ctx: Context<'_>,
+    #[description = "A link to the message or its starboard post"] message: Message,
+) -> Result<(), Error> {
+    moderate_message(ctx, message, false).await
+}
+
+async fn moderate_message(ctx: Context<'_>, message: Message, removed: bool) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let Some(message) = original_guild_message(ctx, guild, &message).await? else {
+        ctx.say("That message is not in this server!").await?;
+        return Ok(());
+    };
+
+    lock_starboard_message(ctx.serenity_context(), ctx.data(), guild, message, removed).await?;
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// The original message of `message` if it is a starboard post, or `message` itself.
+/// Returns `None` if it was not sent in `guild`, so mods can only moderate their own server's messages.
+async fn original_guild_message(
+    ctx: Context<'_>,
+    guild: GuildId,
+    message: &Message,
+) -> Result<Option<MessageId>, Error> {
+    let channel = message.channel_id.to_channel(ctx).await?.guild();
+
+    if channel.is_none_or(|channel| channel.guild_id != guild) {
+        return Ok(None);
+    }
+
+    Ok(Some(original_message_id(ctx.data(), message.id).await?))
+}
+
+/// Lets a locked or removed message back onto the starboards from its next reaction on
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn unlock(
+    ctx: Context<'_>,
+    #[description = "A link to the message or its starboard post"] message: Message,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let Some(message) = original_guild_message(ctx, guild, &message).await? else {
+        ctx.say("That message is not in this server!").await?;
+        return Ok(());
+    };
+
+    if unlock_starboard_message(ctx.data(), guild, message).await? {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("That message is not locked or removed!").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands("blacklist_user", "unblacklist_user", "list_blacklist")
+)]
+pub async fn blacklist(_ctx: Context<'_>, _arg: String) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Keeps a user's messages off the starboards, including the ones already on them
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "add",
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn blacklist_user(
+    ctx: Context<'_>,
+    #[description = "The user to blacklist"] user: User,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    // Their posts might take a while to clean up
+    ctx.defer().await?;
+
+    if add_starboard_blacklist(ctx.serenity_context(), ctx.data(), guild, user.id).await? {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("That user is already blacklisted!").await?;
+    }
+
+    Ok(())
+}
+
+/// Lets a user's messages be starred again
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "remove",
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn unblacklist_user(
+    ctx: Context<'_>,
+    #[description = "The user to remove from the blacklist"] user: User,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    if remove_starboard_blacklist(ctx.data(), guild, user.id).await? {
+        ctx.say("Done!").await?;
+    } else {
+        ctx.say("That user is not blacklisted!").await?;
+    }
+
+    Ok(())
+}
+
+/// Lists the users whose messages can not be starred
+#[poise::command(slash_command, prefix_command, guild_only, rename = "list")]
+pub async fn list_blacklist(ctx: Context<'_>) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let users = get_starboard_blacklist(ctx.data(), guild).await?;
+
+    if users.is_empty() {
+        ctx.say("Nobody is blacklisted!").await?;
+        return Ok(());
+    }
+
+    // An embed so nobody gets pinged
+    let embed = CreateEmbed::new()
+        .title("Starboard blacklist")
+        .description(truncated_mention_list(&users, EMBED_DESCRIPTION_LENGTH))
+        .color(EMBED_COLOR);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Mentions as many of `items` as fit in `max_length` characters, noting how many were left out
+fn truncated_mention_list(items: &[impl Mentionable], max_length: usize) -> String {
+    let mut list = String::new();
+
+    for (i, item) in items.iter().enumerate() {
+        let mention = item.mention().to_string();
+        let rest = format!(" and {} more", items.len() - i);
+
+        if list.len() + mention.len() + 2 + rest.len() > max_length {
+            list.push_str(&rest);
+            break;
+        }
+
+        if !list.is_empty() {
+            list.push_str(", ");
+        }
+
+        list.push_str(&mention);
+    }
+
+    list
+}
+
+#[derive(poise::ChoiceParameter, Clone, Copy)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+    Year,
+    #[name = "All time"]
+    All,
+}
+
+impl Period {
+    fn seconds(self) -> Option<i64> {
+        match self {
+            Period::Day => Some(SECONDS_PER_DAY),
+            Period::Week => Some(7 * SECONDS_PER_DAY),
+            Period::Month => Some(30 * SECONDS_PER_DAY),
+            Period::Year => Some(365 * SECONDS_PER_DAY),
+            Period::All => None,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Period::Day => "today",
+            Period::Week => "this week",
+            Period::Month => "this month",
+            Period::Year => "this year",
+            Period::All => "of all time",
+        }
+    }
+}
+
+/// Shows the most starred messages
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn top(
+    ctx: Context<'_>,
+    #[description = "How far back to look (default: all time)"] period: Option<Period>,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+    let period = period.unwrap_or(Period::All);
+
+    let since = period
+        .seconds()
+        .map(|seconds| Timestamp::now().unix_timestamp() - seconds);
+
+    let starred = get_top_starred(ctx.data(), guild, since, LEADERBOARD_SIZE).await?;
+
+    if starred.is_empty() {
+        ctx.say("No messages have been starred yet!").await?;
+        return Ok(());
+    }
+
+    let mut description = String::new();
+
+    for (i, message) in starred.iter().enumerate() {
+        write!(&mut description, "{}. ", i + 1)?;
+        write_starred_message(ctx, &mut description, message, guild).await?;
+        description.push('\n');
+    }
+
+    let embed = CreateEmbed::new()
+        .title(format!("Most starred messages {}", period.description()))
+        .description(description)
+        .color(EMBED_COLOR);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Shows how many stars a member has received
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn user(
+    ctx: Context<'_>,
+    #[description = "The member to show (default: you)"] member: Option<Member>,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+    let user = member.map_or_else(|| ctx.author().clone(), |member| member.user);
+
+    let stats = get_user_star_stats(ctx.data(), guild, user.id).await?;
+
+    let mut description = format!(
+        "Stars received: {}\nMessages starred: {}",
+        stats.total_stars, stats.starred_messages
+    );
+
+    if let Some(best) = &stats.best {
+        description.push_str("\nBest message: ");
+        write_starred_message(ctx, &mut description, best, guild).await?;
+    }
+
+    let embed = CreateEmbed::new()
+        .title(format!("Stars of {}", user.display_name()))
+        .thumbnail(user.face())
+        .description(description)
+        .color(EMBED_COLOR);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Shows a random starred message
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn random(ctx: Context<'_>) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap();
+
+    let Some(starred) = get_random_starred(ctx.data(), guild).await? else {
+        ctx.say("No messages have been starred yet!").await?;
+        return Ok(());
+    };
+
+    let mut description = String::new();
+    write_starred_message(ctx, &mut description, &starred, guild).await?;
+
+    let embed = CreateEmbed::new()
+        .title("A random highlight")
+        .description(description)
+        .color(EMBED_COLOR);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+async fn write_starred_message(
+    ctx: Context<'_>,
+    out: &mut String,
+    message: &StarredMessage,
+    guild: GuildId,
+) -> Result<(), Error> {
+    write!(out, "{} {}", message.emoji, message.reaction_count)?;
+
+    if let Some(author) = message.author_id {
+        write!(out, " by {}", author.into_serenity().mention())?;
+    }
+
+    let link = message.link(ctx.serenity_context(), guild).await;
+    write!(out, " ([jump]({link}))")?;
 
     Ok(())
 }
@@ -1,142 +1,773 @@
-use crate::{database::IntoDatabase, Context, Error};
+use crate::database::tag_triggers::{compile_trigger, invalidate_tag_triggers, TriggerKind};
+use crate::database::{self, IntoDatabase};
+use crate::serenity::{
+    self, Attachment, AutocompleteChoice, Channel, ChannelId, Color, CreateAllowedMentions,
+    CreateAttachment, CreateEmbed, CreateMessage, Mentionable, Message, Role, Timestamp, UserId,
+};
+use crate::util::{
+    truncate, CHOICE_NAME_LENGTH, EMBED_DESCRIPTION_LENGTH, EMBED_FIELD_LENGTH, EMBED_TITLE_LENGTH,
+    MAX_CONTENT_LENGTH, SECONDS_PER_DAY,
+};
+use crate::{Context, Data, Error, EMBED_COLOR};
+use core::mem;
+use poise::{ChoiceParameter, CreateReply};
+use sqlx::{Postgres, Transaction};
+use std::fmt::Write;
+use url::Url;
+
+/// Discord shows at most this many autocomplete choices
+const MAX_AUTOCOMPLETE_CHOICES: i64 = 25;
+
+/// How many similar tags to suggest when one is not found
+const SUGGESTION_COUNT: i64 = 3;
+
+/// How many tags a search shows
+const SEARCH_RESULT_COUNT: i64 = 10;
+
+/// How much of each tag's description a search shows
+const SEARCH_PREVIEW_LENGTH: usize = 100;
+
+/// How many tags each page of the tag list shows
+const TAGS_PER_PAGE: usize = 10;
+
+/// How much of each tag's description the tag list shows
+const LIST_PREVIEW_LENGTH: usize = 200;
+
+/// Categories are listed as headings, so keep them short
+const MAX_CATEGORY_LENGTH: usize = 50;
+
+/// How many revisions a tag's history shows
+const HISTORY_LENGTH: i64 = 15;
+
+/// How many tags each list of the tag statistics shows
+const STATS_LENGTH: usize = 10;
+
+/// Room kept at the end of a list of tag names for saying how many did not fit
+const OMITTED_NAMES_LENGTH: usize = 16;
+
+/// Tags older than this that have not been used for this long are suggested for removal
+const PRUNE_AFTER_DAYS: i64 = 90;
+
+/// How far back recent uses of a tag go
+const RECENT_DAYS: i64 = 30;
+
+/// How long a trigger waits before replying again when no cooldown is given, in seconds
+const DEFAULT_TRIGGER_COOLDOWN: i32 = 60;
+
+/// The longest cooldown a trigger can have, in seconds
+const MAX_TRIGGER_COOLDOWN: i32 = SECONDS_PER_DAY as i32;
+
+/// Patterns are matched against every message in enabled channels, so keep them short
+const MAX_TRIGGER_LENGTH: usize = 200;
+
+/// The largest file a tag can be sent with, in bytes
+const MAX_ATTACHMENT_SIZE: u32 = 8 * 1024 * 1024;
+
+/// How a tag is sent
+#[derive(sqlx::Type, Clone, Copy, Debug)]
+#[sqlx(type_name = "tag_content", rename_all = "lowercase")]
+enum TagContent {
+    Text,
+    Embed,
+    Attachment,
+}
+
+impl TagContent {
+    /// How the tag is sent, as shown to members
+    fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Embed => "embed",
+            Self::Attachment => "file",
+        }
+    }
+}
+
+/// Everything needed to send a tag
+struct TagRecord {
+    command_name: String,
+    tag_description: String,
+    content_type: TagContent,
+    embed_title: Option<String>,
+    embed_color: Option<i32>,
+    embed_image: Option<String>,
+    attachment_name: Option<String>,
+    attachment: Option<Vec<u8>>,
+}
+
+/// A tag with its variables filled in, ready to be sent
+struct FilledTag {
+    content: Option<String>,
+    embed: Option<CreateEmbed>,
+    attachment: Option<CreateAttachment>,
+}
+
+/// What the variables of a tag are filled in with
+struct TagVariables<'a> {
+    user: String,
+    channel: String,
+    server: String,
+    args: &'a str,
+}
 
 #[poise::command(prefix_command, guild_only, slash_command, aliases("t", "tag"))]
-pub async fn tags(ctx: Context<'_>, arg: String) -> Result<(), Error> {
+pub async fn tags(
+    ctx: Context<'_>,
+    #[description = "The tag to show"]
+    #[autocomplete = "autocomplete_tag"]
+    arg: String,
+    #[description = "Filled into the tag's {args}, and {1}, {2}... one word at a time"]
+    #[rest]
+    args: Option<String>,
+) -> Result<(), Error> {
     // SAFETY: Since this command is guild_only this should NEVER fail
     let guild = ctx.guild_id().unwrap().into_db();
 
-    let possible_tag = sqlx::query!(
-        "SELECT tag_description FROM tag WHERE tag.guild_id = $1 AND tag.command_name = $2",
+    let possible_tag = sqlx::query_as!(
+        TagRecord,
+        r#"SELECT tag.command_name, tag.tag_description, tag.content_type as "content_type: TagContent", tag.embed_title, tag.embed_color,
+            tag.embed_image, tag.attachment_name, tag.attachment
+        FROM tag_name
+        JOIN tag ON tag.guild_id = tag_name.guild_id AND tag.command_name = tag_name.command_name
+        WHERE tag_name.guild_id = $1 AND tag_name.name = $2"#,
         &guild,
         arg
     )
     .fetch_optional(&ctx.data().db)
     .await?;
 
-    match possible_tag {
-        Some(record) => ctx.say(record.tag_description).await?,
-        None => ctx.say("Could not find tag!").await?,
+    let Some(record) = possible_tag else {
+        let suggestions = sqlx::query_scalar!(
+            r#"SELECT name as "name!" FROM tag_name WHERE tag_name.guild_id = $1 AND tag_name.name % $2
+            ORDER BY similarity(tag_name.name, $2) DESC LIMIT $3"#,
+            &guild,
+            arg,
+            SUGGESTION_COUNT
+        )
+        .fetch_all(&ctx.data().db)
+        .await?;
+
+        if suggestions.is_empty() {
+            ctx.say("Could not find tag!").await?;
+        } else {
+            let suggestions = suggestions
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            ctx.say(format!("Could not find tag! Did you mean {suggestions}?"))
+                .await?;
+        }
+
+        return Ok(());
+    };
+
+    let variables = TagVariables {
+        user: ctx.author().mention().to_string(),
+        channel: ctx.channel_id().mention().to_string(),
+        server: ctx
+            .guild()
+            .map(|guild| guild.name.clone())
+            .unwrap_or_default(),
+        args: args.as_deref().unwrap_or_default(),
+    };
+
+    let command_name = record.command_name.clone();
+    let tag = fill_tag(record, &variables);
+
+    // Arguments can contain anything, so tags never ping
+    let mut reply = CreateReply::default().allowed_mentions(CreateAllowedMentions::new());
+
+    if let Some(content) = tag.content {
+        reply = reply.content(content);
+    }
+
+    if let Some(embed) = tag.embed {
+        reply = reply.embed(embed);
+    }
+
+    if let Some(attachment) = tag.attachment {
+        reply = reply.attachment(attachment);
+    }
+
+    ctx.send(reply).await?;
+
+    record_tag_usage(
+        ctx.data(),
+        guild,
+        &command_name,
+        ctx.author().id,
+        ctx.channel_id(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Replies to a message with the tag one of its triggers matched
+pub async fn send_triggered_tag(
+    ctx: &serenity::Context,
+    data: &Data,
+    message: &Message,
+    name: &str,
+) -> Result<(), Error> {
+    let Some(guild) = message.guild_id else {
+        return Ok(());
+    };
+
+    let possible_tag = sqlx::query_as!(
+        TagRecord,
+        r#"SELECT tag.command_name, tag.tag_description, tag.content_type as "content_type: TagContent", tag.embed_title, tag.embed_color,
+            tag.embed_image, tag.attachment_name, tag.attachment
+        FROM tag WHERE tag.guild_id = $1 AND tag.command_name = $2"#,
+        guild.into_db(),
+        name
+    )
+    .fetch_optional(&data.db)
+    .await?;
+
+    // The tag might have been removed since the triggers were loaded
+    let Some(record) = possible_tag else {
+        return Ok(());
+    };
+
+    let variables = TagVariables {
+        user: message.author.mention().to_string(),
+        channel: message.channel_id.mention().to_string(),
+        server: guild.name(ctx).unwrap_or_default(),
+        args: "",
+    };
+
+    let tag = fill_tag(record, &variables);
+
+    let mut reply = CreateMessage::new()
+        .reference_message(message)
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    if let Some(content) = tag.content {
+        reply = reply.content(content);
+    }
+
+    if let Some(embed) = tag.embed {
+        reply = reply.embed(embed);
+    }
+
+    if let Some(attachment) = tag.attachment {
+        reply = reply.add_file(attachment);
+    }
+
+    message.channel_id.send_message(ctx, reply).await?;
+
+    record_tag_usage(
+        data,
+        guild.into_db(),
+        name,
+        message.author.id,
+        message.channel_id,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Fills the variables into a tag, laid out the way it is set up to be sent
+fn fill_tag(record: TagRecord, variables: &TagVariables<'_>) -> FilledTag {
+    let description = fill_tag_variables(&record.tag_description, variables);
+
+    match record.content_type {
+        TagContent::Text => FilledTag {
+            content: Some(truncate(&description, MAX_CONTENT_LENGTH)),
+            embed: None,
+            attachment: None,
+        },
+        TagContent::Embed => {
+            let mut embed =
+                CreateEmbed::new().description(truncate(&description, EMBED_DESCRIPTION_LENGTH));
+
+            if let Some(title) = record.embed_title {
+                let title = fill_tag_variables(&title, variables);
+                embed = embed.title(truncate(&title, EMBED_TITLE_LENGTH));
+            }
+
+            if let Some(color) = record.embed_color {
+                embed = embed.color(color as u32);
+            }
+
+            if let Some(image) = record.embed_image {
+                embed = embed.image(image);
+            }
+
+            FilledTag {
+                content: None,
+                embed: Some(embed),
+                attachment: None,
+            }
+        }
+        TagContent::Attachment => FilledTag {
+            content: Some(truncate(&description, MAX_CONTENT_LENGTH)),
+            embed: None,
+            attachment: match (record.attachment_name, record.attachment) {
+                (Some(name), Some(file)) => Some(CreateAttachment::bytes(file, name)),
+                _ => None,
+            },
+        },
+    }
+}
+
+async fn record_tag_usage(
+    data: &Data,
+    guild: i64,
+    name: &str,
+    user: UserId,
+    channel: ChannelId,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "INSERT INTO tag_usage (guild_id, command_name, user_id, channel_id) VALUES ($1, $2, $3, $4)",
+        guild,
+        name,
+        user.into_db(),
+        channel.into_db()
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(())
+}
+
+/// Fills `{user}`, `{channel}`, `{server}`, `{args}` and `{1}`, `{2}`... for each word of the arguments into a tag.
+/// Anything else in braces, including positions past the last argument, is left as is.
+fn fill_tag_variables(template: &str, variables: &TagVariables<'_>) -> String {
+    let words: Vec<&str> = variables.args.split_whitespace().collect();
+
+    let mut content = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        content.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+
+        let name = &rest[1..end];
+
+        let position = name
+            .parse::<usize>()
+            .ok()
+            .and_then(|position| position.checked_sub(1))
+            .and_then(|index| words.get(index));
+
+        match name {
+            "user" => content.push_str(&variables.user),
+            "channel" => content.push_str(&variables.channel),
+            "server" => content.push_str(&variables.server),
+            "args" => content.push_str(variables.args),
+            _ => match position {
+                Some(word) => content.push_str(word),
+                None => content.push_str(&rest[..=end]),
+            },
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    content.push_str(rest);
+    content
+}
+
+/// The name of the tag `name` refers to, either itself or the tag it is an alias of
+async fn resolve_tag_name(
+    ctx: Context<'_>,
+    guild: i64,
+    name: &str,
+) -> Result<Option<String>, Error> {
+    let command_name = sqlx::query_scalar!(
+        r#"SELECT command_name as "command_name!" FROM tag_name WHERE tag_name.guild_id = $1 AND tag_name.name = $2"#,
+        guild,
+        name
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?;
+
+    Ok(command_name)
+}
+
+/// Suggests the names and aliases of tags starting with what has been typed so far, followed by those close to it
+async fn autocomplete_tag(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Some(guild) = ctx.guild_id() else {
+        return Vec::new();
     };
 
+    sqlx::query_scalar!(
+        r#"SELECT name as "name!" FROM tag_name
+        WHERE tag_name.guild_id = $1 AND (starts_with(lower(tag_name.name), lower($2)) OR tag_name.name % $2)
+        ORDER BY starts_with(lower(tag_name.name), lower($2)) DESC, similarity(tag_name.name, $2) DESC, tag_name.name
+        LIMIT $3"#,
+        guild.into_db(),
+        partial,
+        MAX_AUTOCOMPLETE_CHOICES
+    )
+    .fetch_all(&ctx.data().db)
+    .await
+    .unwrap_or_default()
+}
+
+/// Searches the names and descriptions of tags
+#[poise::command(prefix_command, guild_only, slash_command, aliases("tag-search"))]
+pub async fn tag_search(ctx: Context<'_>, #[rest] text: String) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let tags = sqlx::query!(
+        "SELECT command_name, tag_description FROM tag
+        WHERE tag.guild_id = $1 AND (tag.command_name % $2 OR tag.command_name ILIKE '%' || $4 || '%'
+            OR to_tsvector('english', tag.tag_description) @@ websearch_to_tsquery('english', $2))
+        ORDER BY similarity(tag.command_name, $2)
+            + ts_rank(to_tsvector('english', tag.tag_description), websearch_to_tsquery('english', $2)) DESC
+        LIMIT $3",
+        guild,
+        text,
+        SEARCH_RESULT_COUNT,
+        escape_like(&text)
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    if tags.is_empty() {
+        ctx.say("No tags matched your search").await?;
+        return Ok(());
+    }
+
+    let mut content = String::new();
+
+    for tag in tags {
+        let preview = tag.tag_description.lines().next().unwrap_or_default();
+        write!(
+            &mut content,
+            "> {}\n{}\n\n",
+            tag.command_name,
+            truncate(preview, SEARCH_PREVIEW_LENGTH)
+        )?;
+    }
+
+    // Tags can contain anything, so search results never ping
+    let reply = CreateReply::default()
+        .content(truncate(&content, MAX_CONTENT_LENGTH))
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    ctx.send(reply).await?;
+
     Ok(())
 }
 
+/// Escapes the wildcards of `LIKE` patterns, so `text` is only matched as itself
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Lists this server's tags, optionally only those in one category
 #[poise::command(prefix_command, guild_only, slash_command, aliases("tag-list"))]
-pub async fn tag_list(ctx: Context<'_>) -> Result<(), Error> {
+pub async fn tag_list(
+    ctx: Context<'_>,
+    #[description = "Only list the tags in this category"]
+    #[autocomplete = "autocomplete_category"]
+    category: Option<String>,
+) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
     // SAFETY: Since this command is guild_only this should NEVER fail
     let guild = ctx.guild_id().unwrap().into_db();
 
     let tags = sqlx::query!(
-        "SELECT command_name, tag_description FROM tag WHERE tag.guild_id = $1",
-        guild
+        r#"SELECT command_name, tag_description, category,
+            ARRAY(SELECT alias FROM tag_alias WHERE tag_alias.guild_id = tag.guild_id AND tag_alias.command_name = tag.command_name ORDER BY alias) as "aliases!"
+        FROM tag WHERE tag.guild_id = $1 AND ($2::TEXT IS NULL OR LOWER(tag.category) = LOWER($2))
+        ORDER BY tag.category NULLS LAST, tag.command_name"#,
+        guild,
+        category
     )
     .fetch_all(&ctx.data().db)
     .await?;
 
-    //TODO make this with embeds
+    if tags.is_empty() {
+        match category {
+            Some(category) => {
+                ctx.say(format!(
+                    "No tags in the category {category} have been found"
+                ))
+                .await?;
+            }
+            None => {
+                ctx.say("No tags for this server have been found").await?;
+            }
+        }
 
-    let mut content;
+        return Ok(());
+    }
 
-    let content = match tags.len() {
-        0 => "No tags for this server have been found",
-        _ => {
-            content = String::new();
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut page_tags = 0;
+    let mut current_category = None;
 
-            for tag in tags {
-                content += &format!("> {}\n{}\n\n", tag.command_name, tag.tag_description);
-            }
+    for tag in &tags {
+        let mut entry = String::new();
 
-            &content
+        if tag.aliases.is_empty() {
+            writeln!(&mut entry, "**{}**", tag.command_name)?;
+        } else {
+            writeln!(
+                &mut entry,
+                "**{}** ({})",
+                tag.command_name,
+                truncate(&tag.aliases.join(", "), LIST_PREVIEW_LENGTH)
+            )?;
+        }
+
+        writeln!(
+            &mut entry,
+            "{}",
+            truncate(&tag.tag_description, LIST_PREVIEW_LENGTH)
+        )?;
+
+        let heading = format!(
+            "## {}\n",
+            tag.category.as_deref().unwrap_or("Uncategorized")
+        );
+
+        // Pages are shown as embed descriptions, so they end early if the next tag would not fit
+        let length = page.chars().count() + heading.chars().count() + entry.chars().count();
+
+        if !page.is_empty() && (page_tags == TAGS_PER_PAGE || length > EMBED_DESCRIPTION_LENGTH) {
+            pages.push(mem::take(&mut page));
+            page_tags = 0;
+        }
+
+        // Every page starts with its category, so a page never lacks context
+        if page.is_empty() || tag.category.as_deref() != current_category {
+            current_category = tag.category.as_deref();
+            page.push_str(&heading);
         }
-    };
 
-    ctx.say(content).await?;
+        page.push_str(&entry);
+        page_tags += 1;
+    }
+
+    pages.push(page);
+
+    let pages: Vec<&str> = pages.iter().map(String::as_str).collect();
+
+    poise::builtins::paginate(ctx, &pages).await?;
 
     Ok(())
 }
 
+/// Suggests the categories this server's tags are sorted into
+async fn autocomplete_category(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Some(guild) = ctx.guild_id() else {
+        return Vec::new();
+    };
+
+    sqlx::query_scalar!(
+        r#"SELECT DISTINCT category as "category!" FROM tag
+        WHERE guild_id = $1 AND category IS NOT NULL AND category ILIKE '%' || $2 || '%'
+        ORDER BY category LIMIT $3"#,
+        guild.into_db(),
+        partial,
+        MAX_AUTOCOMPLETE_CHOICES
+    )
+    .fetch_all(&ctx.data().db)
+    .await
+    .unwrap_or_default()
+}
+
 #[poise::command(
     prefix_command,
     slash_command,
     aliases("tag-edit"),
-    subcommands("add", "edit", "remove")
+    subcommands(
+        "add", "edit", "remove", "alias", "embed", "attach", "plain", "history", "revert",
+        "members", "stats", "info", "category", "trigger"
+    )
 )]
 pub async fn tag_edit(_ctx: Context<'_>, _arg: String) -> Result<(), Error> {
     Ok(())
 }
 
-#[poise::command(
-    slash_command,
-    prefix_command,
-    guild_only,
-    track_edits,
-    required_permissions = "MANAGE_MESSAGES"
-)]
+#[poise::command(slash_command, prefix_command, guild_only, track_edits)]
 async fn add(ctx: Context<'_>, name: String, #[rest] description: String) -> Result<(), Error> {
     // SAFETY: Since this command is guild_only this should NEVER fail
     let guild = ctx.guild_id().unwrap().into_db();
 
+    let moderator = is_tag_moderator(ctx).await?;
+
+    if !moderator {
+        let member_tags = sqlx::query_scalar!(
+            "SELECT member_tags FROM guild WHERE guild.discord_id = $1",
+            guild
+        )
+        .fetch_optional(&ctx.data().db)
+        .await?;
+
+        if member_tags != Some(true) {
+            ctx.say("Only moderators can add tags in this server!")
+                .await?;
+            return Ok(());
+        }
+    }
+
+    if resolve_tag_name(ctx, guild, &name).await?.is_some() {
+        ctx.say("There is already a tag or alias with that name!")
+            .await?;
+        return Ok(());
+    }
+
+    let mut tx = ctx.data().db.begin().await?;
+
+    // Tags added by members belong to them, those added by moderators to every moderator
     sqlx::query!(
-        "INSERT INTO tag (guild_id, command_name, tag_description) VALUES ($1, $2, $3)",
+        "INSERT INTO tag (guild_id, command_name, tag_description, creator_id, member_owned) VALUES ($1, $2, $3, $4, $5)",
         guild,
         name,
-        description
+        description,
+        ctx.author().id.into_db(),
+        !moderator
     )
-    .execute(&ctx.data().db)
+    .execute(&mut *tx)
     .await?;
 
+    record_tag_revision(&mut tx, ctx.author().id, guild, &name).await?;
+    tx.commit().await?;
+
     ctx.say("Done!").await?;
 
     Ok(())
 }
 
-#[poise::command(
-    slash_command,
-    prefix_command,
-    guild_only,
-    track_edits,
-    required_permissions = "MANAGE_MESSAGES"
-)]
+#[poise::command(slash_command, prefix_command, guild_only, track_edits)]
 async fn edit(
     ctx: Context<'_>,
-    name: String,
+    #[autocomplete = "autocomplete_tag"] name: String,
     #[rest] new_description: String,
 ) -> Result<(), Error> {
     // SAFETY: Since this command is guild_only this should NEVER fail
     let guild = ctx.guild_id().unwrap().into_db();
 
+    // Editing through an alias edits the tag itself, so every alias stays the same
+    let Some(name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    if !can_edit_tag(ctx, guild, &name).await? {
+        ctx.say("Only moderators and the member who added this tag can change it!")
+            .await?;
+        return Ok(());
+    }
+
+    let mut tx = ctx.data().db.begin().await?;
+
     sqlx::query!(
         "UPDATE tag SET tag_description = $1 WHERE tag.guild_id = $2 AND tag.command_name = $3",
         new_description,
         guild,
         name
     )
+    .execute(&mut *tx)
+    .await?;
+
+    record_tag_revision(&mut tx, ctx.author().id, guild, &name).await?;
+    tx.commit().await?;
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, prefix_command, guild_only, aliases("rem"), track_edits)]
+async fn remove(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_tag"] name: String,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild_id = ctx.guild_id().unwrap();
+    let guild = guild_id.into_db();
+
+    let Some(command_name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    if !can_edit_tag(ctx, guild, &command_name).await? {
+        ctx.say("Only moderators and the member who added this tag can change it!")
+            .await?;
+        return Ok(());
+    }
+
+    // Removing an alias leaves its tag alone, removing a tag takes its aliases with it
+    let removed_alias = sqlx::query!(
+        "DELETE FROM tag_alias WHERE tag_alias.guild_id = $1 AND tag_alias.alias = $2",
+        guild,
+        name
+    )
     .execute(&ctx.data().db)
     .await?;
 
+    if removed_alias.rows_affected() == 0 {
+        sqlx::query!(
+            "DELETE FROM tag WHERE tag.guild_id = $1 AND tag.command_name = $2",
+            guild,
+            name
+        )
+        .execute(&ctx.data().db)
+        .await?;
+
+        // The tag's triggers are removed with it
+        invalidate_tag_triggers(ctx.data(), guild_id);
+    }
+
     ctx.say("Done!").await?;
 
     Ok(())
 }
 
-#[poise::command(
-    slash_command,
-    prefix_command,
-    guild_only,
-    aliases("rem"),
-    track_edits,
-    required_permissions = "MANAGE_MESSAGES"
-)]
-async fn remove(ctx: Context<'_>, name: String) -> Result<(), Error> {
+/// Lets an existing tag also be used by another name
+#[poise::command(slash_command, prefix_command, guild_only, track_edits)]
+async fn alias(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_tag"] existing: String,
+    alias: String,
+) -> Result<(), Error> {
     // SAFETY: Since this command is guild_only this should NEVER fail
     let guild = ctx.guild_id().unwrap().into_db();
 
+    // Aliases of aliases point straight at the tag
+    let Some(existing) = resolve_tag_name(ctx, guild, &existing).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    if !can_edit_tag(ctx, guild, &existing).await? {
+        ctx.say("Only moderators and the member who added this tag can change it!")
+            .await?;
+        return Ok(());
+    }
+
+    if resolve_tag_name(ctx, guild, &alias).await?.is_some() {
+        ctx.say("There is already a tag or alias with that name!")
+            .await?;
+        return Ok(());
+    }
+
     sqlx::query!(
-        "DELETE FROM tag WHERE tag.guild_id = $1 AND tag.command_name = $2",
+        "INSERT INTO tag_alias (guild_id, alias, command_name) VALUES ($1, $2, $3)",
         guild,
-        name
+        alias,
+        existing
     )
     .execute(&ctx.data().db)
     .await?;
@@ -145,3 +776,1052 @@ async fn remove(ctx: Context<'_>, name: String) -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Sends a tag as an embed, its description becomes the embed's
+#[poise::command(slash_command, prefix_command, guild_only, track_edits)]
+async fn embed(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_tag"] name: String,
+    #[description = "The title of the embed, variables get filled in"] title: Option<String>,
+    #[description = "The color of the embed as hex, like #5865F2"] color: Option<String>,
+    #[description = "A link to an image to show in the embed"] image: Option<String>,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let color = match color.as_deref().map(parse_color) {
+        Some(Some(color)) => Some(i32::try_from(color.0)?),
+        Some(None) => {
+            ctx.say("That is not a hex color!").await?;
+            return Ok(());
+        }
+        None => None,
+    };
+
+    if title
+        .as_ref()
+        .is_some_and(|title| title.chars().count() > EMBED_TITLE_LENGTH)
+    {
+        ctx.say(format!(
+            "The title can be at most {EMBED_TITLE_LENGTH} characters long!"
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    if image.as_deref().is_some_and(|image| !is_web_link(image)) {
+        ctx.say("The image has to be an http or https link!")
+            .await?;
+        return Ok(());
+    }
+
+    let Some(name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    if !can_edit_tag(ctx, guild, &name).await? {
+        ctx.say("Only moderators and the member who added this tag can change it!")
+            .await?;
+        return Ok(());
+    }
+
+    let mut tx = ctx.data().db.begin().await?;
+
+    sqlx::query!(
+        "UPDATE tag SET content_type = 'embed', embed_title = $3, embed_color = $4, embed_image = $5, attachment_name = NULL, attachment = NULL
+        WHERE tag.guild_id = $1 AND tag.command_name = $2",
+        guild,
+        name,
+        title,
+        color,
+        image
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    record_tag_revision(&mut tx, ctx.author().id, guild, &name).await?;
+    tx.commit().await?;
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// Sends a tag with a file, its description becomes the message sent along with it
+#[poise::command(slash_command, prefix_command, guild_only, track_edits)]
+async fn attach(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_tag"] name: String,
+    #[description = "The file to send with the tag"] file: Attachment,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    if file.size > MAX_ATTACHMENT_SIZE {
+        ctx.say("That file is too big to attach to a tag!").await?;
+        return Ok(());
+    }
+
+    let Some(name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    if !can_edit_tag(ctx, guild, &name).await? {
+        ctx.say("Only moderators and the member who added this tag can change it!")
+            .await?;
+        return Ok(());
+    }
+
+    // Links to uploaded files expire, so the file itself is kept
+    let bytes = file.download().await?;
+
+    let mut tx = ctx.data().db.begin().await?;
+
+    sqlx::query!(
+        "UPDATE tag SET content_type = 'attachment', attachment_name = $3, attachment = $4, embed_title = NULL, embed_color = NULL, embed_image = NULL
+        WHERE tag.guild_id = $1 AND tag.command_name = $2",
+        guild,
+        name,
+        file.filename,
+        bytes
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    record_tag_revision(&mut tx, ctx.author().id, guild, &name).await?;
+    tx.commit().await?;
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// Sends a tag as plain text again, without its embed or file
+#[poise::command(slash_command, prefix_command, guild_only, track_edits)]
+async fn plain(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_tag"] name: String,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let Some(name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    if !can_edit_tag(ctx, guild, &name).await? {
+        ctx.say("Only moderators and the member who added this tag can change it!")
+            .await?;
+        return Ok(());
+    }
+
+    let mut tx = ctx.data().db.begin().await?;
+
+    sqlx::query!(
+        "UPDATE tag SET content_type = 'text', embed_title = NULL, embed_color = NULL, embed_image = NULL, attachment_name = NULL, attachment = NULL
+        WHERE tag.guild_id = $1 AND tag.command_name = $2",
+        guild,
+        name
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    record_tag_revision(&mut tx, ctx.author().id, guild, &name).await?;
+    tx.commit().await?;
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// Moves a tag into a category, or out of its category when none is given
+#[poise::command(slash_command, prefix_command, guild_only, track_edits)]
+async fn category(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_tag"] name: String,
+    #[autocomplete = "autocomplete_category"]
+    #[rest]
+    category: Option<String>,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let Some(name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    if !can_edit_tag(ctx, guild, &name).await? {
+        ctx.say("Only moderators and the member who added this tag can change it!")
+            .await?;
+        return Ok(());
+    }
+
+    if category
+        .as_ref()
+        .is_some_and(|category| category.chars().count() > MAX_CATEGORY_LENGTH)
+    {
+        ctx.say(format!(
+            "Categories can be at most {MAX_CATEGORY_LENGTH} characters long!"
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    // Reuse the spelling of an existing category, so one category is not listed twice
+    let category = match category {
+        Some(category) => Some(
+            sqlx::query_scalar!(
+                r#"SELECT category as "category!" FROM tag
+                WHERE guild_id = $1 AND LOWER(category) = LOWER($2) LIMIT 1"#,
+                guild,
+                category.trim()
+            )
+            .fetch_optional(&ctx.data().db)
+            .await?
+            .unwrap_or_else(|| category.trim().to_string()),
+        ),
+        None => None,
+    };
+
+    sqlx::query!(
+        "UPDATE tag SET category = $3 WHERE tag.guild_id = $1 AND tag.command_name = $2",
+        guild,
+        name,
+        category
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// Shows who changed a tag and when
+#[poise::command(slash_command, prefix_command, guild_only)]
+async fn history(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_tag"] name: String,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let Some(name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    let tag = sqlx::query!(
+        r#"SELECT creator_id as "creator_id: database::UserId", last_editor_id as "last_editor_id: database::UserId"
+        FROM tag WHERE tag.guild_id = $1 AND tag.command_name = $2"#,
+        guild,
+        name
+    )
+    .fetch_one(&ctx.data().db)
+    .await?;
+
+    let revisions = sqlx::query!(
+        r#"SELECT revision, content_type as "content_type: TagContent", editor_id as "editor_id: database::UserId",
+            EXTRACT(EPOCH FROM edited_at)::BIGINT as "edited_at!"
+        FROM tag_revision WHERE tag_revision.guild_id = $1 AND tag_revision.command_name = $2
+        ORDER BY revision DESC LIMIT $3"#,
+        guild,
+        name,
+        HISTORY_LENGTH
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    let mut description = format!(
+        "Added by {}, last changed by {}\n",
+        someone(tag.creator_id),
+        someone(tag.last_editor_id)
+    );
+
+    for revision in revisions {
+        write!(
+            &mut description,
+            "\n**#{}** by {} <t:{}:R> ({})",
+            revision.revision,
+            someone(revision.editor_id),
+            revision.edited_at,
+            revision.content_type.name()
+        )?;
+    }
+
+    let embed = CreateEmbed::new()
+        .title(format!("History of {name}"))
+        .description(description)
+        .color(EMBED_COLOR);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Brings a tag back to how it was at an earlier revision
+#[poise::command(slash_command, prefix_command, guild_only, track_edits)]
+async fn revert(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_tag"] name: String,
+    #[description = "The revision to go back to, see the tag's history"] revision: i32,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let Some(name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    if !can_edit_tag(ctx, guild, &name).await? {
+        ctx.say("Only moderators and the member who added this tag can change it!")
+            .await?;
+        return Ok(());
+    }
+
+    let mut tx = ctx.data().db.begin().await?;
+
+    let reverted = sqlx::query!(
+        "UPDATE tag SET tag_description = tag_revision.tag_description, content_type = tag_revision.content_type,
+            embed_title = tag_revision.embed_title, embed_color = tag_revision.embed_color, embed_image = tag_revision.embed_image,
+            attachment_name = tag_revision.attachment_name,
+            attachment = (SELECT attachment FROM tag_attachment WHERE tag_attachment.id = tag_revision.attachment_id)
+        FROM tag_revision
+        WHERE tag.guild_id = $1 AND tag.command_name = $2
+            AND tag_revision.guild_id = tag.guild_id AND tag_revision.command_name = tag.command_name AND tag_revision.revision = $3",
+        guild,
+        name,
+        revision
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if reverted.rows_affected() == 0 {
+        ctx.say("That tag has no such revision!").await?;
+        return Ok(());
+    }
+
+    // Going back is a change of its own, so it can be undone too
+    record_tag_revision(&mut tx, ctx.author().id, guild, &name).await?;
+    tx.commit().await?;
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// Lets members without moderator permissions add tags of their own
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+async fn members(
+    ctx: Context<'_>,
+    #[description = "Whether members can add tags"] allowed: bool,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let updated = sqlx::query!(
+        "UPDATE guild SET member_tags = $1 WHERE guild.discord_id = $2",
+        allowed,
+        guild
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        ctx.say("This server is not set up yet, run `/guild init` first!")
+            .await?;
+        return Ok(());
+    }
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// How much each tag is used
+struct TagUsage {
+    command_name: String,
+    uses: i64,
+    /// When the tag was last used, as a unix timestamp
+    last_used: Option<i64>,
+    /// When the tag was added, as a unix timestamp
+    created: Option<i64>,
+}
+
+/// Shows which tags are used the most, which were used lately and which are never used
+#[poise::command(slash_command, prefix_command, guild_only)]
+async fn stats(ctx: Context<'_>) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let mut usage = sqlx::query_as!(
+        TagUsage,
+        r#"SELECT tag.command_name, COUNT(tag_usage.used_at) as "uses!",
+            EXTRACT(EPOCH FROM MAX(tag_usage.used_at))::BIGINT as last_used,
+            (SELECT EXTRACT(EPOCH FROM MIN(tag_revision.edited_at))::BIGINT FROM tag_revision
+                WHERE tag_revision.guild_id = tag.guild_id AND tag_revision.command_name = tag.command_name) as created
+        FROM tag
+        LEFT JOIN tag_usage ON tag_usage.guild_id = tag.guild_id AND tag_usage.command_name = tag.command_name
+        WHERE tag.guild_id = $1
+        GROUP BY tag.guild_id, tag.command_name
+        ORDER BY tag.command_name"#,
+        guild
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    if usage.is_empty() {
+        ctx.say("No tags for this server have been found").await?;
+        return Ok(());
+    }
+
+    let prune_before = Timestamp::now().unix_timestamp() - PRUNE_AFTER_DAYS * SECONDS_PER_DAY;
+
+    let never_used: Vec<&str> = usage
+        .iter()
+        .filter(|tag| tag.uses == 0)
+        .map(|tag| tag.command_name.as_str())
+        .collect();
+
+    // Tags that have been around long enough to have been used, but have not been lately
+    let prunable: Vec<&str> = usage
+        .iter()
+        .filter(|tag| tag.created.is_some_and(|created| created < prune_before))
+        .filter(|tag| tag.last_used.is_none_or(|used| used < prune_before))
+        .map(|tag| tag.command_name.as_str())
+        .collect();
+
+    let prunable = name_list(&prunable);
+    let never_used = name_list(&never_used);
+
+    usage.sort_by_key(|tag| core::cmp::Reverse(tag.uses));
+
+    let mut most_used = String::new();
+
+    for tag in usage.iter().filter(|tag| tag.uses > 0).take(STATS_LENGTH) {
+        writeln!(&mut most_used, "`{}` {} uses", tag.command_name, tag.uses)?;
+    }
+
+    usage.sort_by_key(|tag| core::cmp::Reverse(tag.last_used));
+
+    let mut recently_used = String::new();
+
+    for tag in usage.iter().take(STATS_LENGTH) {
+        let Some(last_used) = tag.last_used else {
+            break;
+        };
+
+        writeln!(
+            &mut recently_used,
+            "`{}` <t:{last_used}:R>",
+            tag.command_name
+        )?;
+    }
+
+    let or_none = |list: String| {
+        if list.is_empty() {
+            "None".to_string()
+        } else {
+            truncate(&list, EMBED_FIELD_LENGTH)
+        }
+    };
+
+    let embed = CreateEmbed::new()
+        .title("Tag statistics")
+        .field("Most used", or_none(most_used), false)
+        .field("Recently used", or_none(recently_used), false)
+        .field("Never used", or_none(never_used), false)
+        .field(
+            format!("Unused for over {PRUNE_AFTER_DAYS} days, could be removed"),
+            or_none(prunable),
+            false,
+        )
+        .color(EMBED_COLOR);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Shows everything about a single tag, including how much it is used
+#[poise::command(slash_command, prefix_command, guild_only)]
+async fn info(
+    ctx: Context<'_>,
+    #[autocomplete = "autocomplete_tag"] name: String,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let Some(name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    let recent_since = Timestamp::now().unix_timestamp() - RECENT_DAYS * SECONDS_PER_DAY;
+
+    let tag = sqlx::query!(
+        r#"SELECT tag.content_type as "content_type: TagContent", tag.member_owned, tag.category,
+            tag.creator_id as "creator_id: database::UserId", tag.last_editor_id as "last_editor_id: database::UserId",
+            ARRAY(SELECT alias FROM tag_alias WHERE tag_alias.guild_id = tag.guild_id AND tag_alias.command_name = tag.command_name ORDER BY alias) as "aliases!",
+            (SELECT COUNT(*) FROM tag_revision WHERE tag_revision.guild_id = tag.guild_id AND tag_revision.command_name = tag.command_name) as "revisions!",
+            (SELECT COUNT(*) FROM tag_usage WHERE tag_usage.guild_id = tag.guild_id AND tag_usage.command_name = tag.command_name) as "uses!",
+            (SELECT COUNT(*) FROM tag_usage WHERE tag_usage.guild_id = tag.guild_id AND tag_usage.command_name = tag.command_name
+                AND tag_usage.used_at >= to_timestamp($3)) as "recent_uses!",
+            (SELECT COUNT(DISTINCT user_id) FROM tag_usage WHERE tag_usage.guild_id = tag.guild_id AND tag_usage.command_name = tag.command_name) as "users!",
+            (SELECT EXTRACT(EPOCH FROM MAX(used_at))::BIGINT FROM tag_usage
+                WHERE tag_usage.guild_id = tag.guild_id AND tag_usage.command_name = tag.command_name) as last_used,
+            (SELECT channel_id FROM tag_usage WHERE tag_usage.guild_id = tag.guild_id AND tag_usage.command_name = tag.command_name
+                GROUP BY channel_id ORDER BY COUNT(*) DESC LIMIT 1) as "top_channel: database::ChannelId"
+        FROM tag WHERE tag.guild_id = $1 AND tag.command_name = $2"#,
+        guild,
+        name,
+        recent_since as f64
+    )
+    .fetch_one(&ctx.data().db)
+    .await?;
+
+    let mut description = format!(
+        "Sent as: {}\n\
+        Added by: {}{}\n\
+        Last changed by: {} ({} revisions)\n\
+        Used: {} times, {} in the last {RECENT_DAYS} days, by {} members",
+        tag.content_type.name(),
+        someone(tag.creator_id),
+        if tag.member_owned {
+            ", who owns it"
+        } else {
+            ""
+        },
+        someone(tag.last_editor_id),
+        tag.revisions,
+        tag.uses,
+        tag.recent_uses,
+        tag.users,
+    );
+
+    if let Some(last_used) = tag.last_used {
+        write!(&mut description, "\nLast used: <t:{last_used}:R>")?;
+    }
+
+    if let Some(channel) = tag.top_channel {
+        write!(
+            &mut description,
+            "\nMostly used in: {}",
+            channel.into_serenity().mention()
+        )?;
+    }
+
+    if let Some(category) = tag.category {
+        write!(&mut description, "\nCategory: {category}")?;
+    }
+
+    // Last, as a tag with many aliases is cut off there
+    if !tag.aliases.is_empty() {
+        write!(&mut description, "\nAliases: {}", tag.aliases.join(", "))?;
+    }
+
+    let embed = CreateEmbed::new()
+        .title(truncate(&name, EMBED_TITLE_LENGTH))
+        .description(truncate(&description, EMBED_DESCRIPTION_LENGTH))
+        .color(EMBED_COLOR);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Tag names in code blocks, as many as fit in an embed field
+fn name_list(names: &[&str]) -> String {
+    let mut list = String::new();
+
+    for (i, name) in names.iter().enumerate() {
+        let entry = format!("`{name}` ");
+
+        if list.chars().count() + entry.chars().count() + OMITTED_NAMES_LENGTH > EMBED_FIELD_LENGTH
+        {
+            write!(&mut list, "and {} more", names.len() - i).unwrap();
+            break;
+        }
+
+        list.push_str(&entry);
+    }
+
+    list
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands(
+        "add_trigger",
+        "remove_trigger",
+        "list_triggers",
+        "trigger_channel",
+        "ignore_role"
+    )
+)]
+async fn trigger(_ctx: Context<'_>, _arg: String) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Replies with a tag whenever a message in an enabled channel matches a pattern
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "add",
+    required_permissions = "MANAGE_MESSAGES"
+)]
+async fn add_trigger(
+    ctx: Context<'_>,
+    #[description = "The tag to reply with"]
+    #[autocomplete = "autocomplete_tag"]
+    name: String,
+    #[description = "How the pattern is matched"] kind: TriggerKind,
+    #[description = "The keyword, glob or regex messages are matched against"] pattern: String,
+    #[description = "Seconds before this trigger can reply again, 60 by default"] cooldown: Option<
+        i32,
+    >,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild_id = ctx.guild_id().unwrap();
+    let guild = guild_id.into_db();
+
+    let Some(name) = resolve_tag_name(ctx, guild, &name).await? else {
+        ctx.say("Could not find tag!").await?;
+        return Ok(());
+    };
+
+    let cooldown = cooldown.unwrap_or(DEFAULT_TRIGGER_COOLDOWN);
+
+    if !(0..=MAX_TRIGGER_COOLDOWN).contains(&cooldown) {
+        ctx.say(format!(
+            "Cooldowns have to be between 0 and {MAX_TRIGGER_COOLDOWN} seconds!"
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let pattern = pattern.trim();
+
+    if pattern.is_empty() || pattern.chars().count() > MAX_TRIGGER_LENGTH {
+        ctx.say(format!(
+            "Patterns have to be between 1 and {MAX_TRIGGER_LENGTH} characters long!"
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    if let Err(err) = compile_trigger(kind, pattern) {
+        ctx.say(format!("That is not a valid pattern: {err}"))
+            .await?;
+        return Ok(());
+    }
+
+    let id = sqlx::query_scalar!(
+        "INSERT INTO tag_trigger (guild_id, command_name, kind, pattern, cooldown_seconds) VALUES ($1, $2, $3, $4, $5) RETURNING id",
+        guild,
+        name,
+        kind as TriggerKind,
+        pattern,
+        cooldown
+    )
+    .fetch_one(&ctx.data().db)
+    .await?;
+
+    invalidate_tag_triggers(ctx.data(), guild_id);
+
+    let enabled_channels = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM tag_trigger_channel WHERE guild_id = $1"#,
+        guild
+    )
+    .fetch_one(&ctx.data().db)
+    .await?;
+
+    if enabled_channels == 0 {
+        ctx.say(format!(
+            "Added trigger {id}! Triggers only reply in channels they are enabled in, which none are yet"
+        ))
+        .await?;
+    } else {
+        ctx.say(format!("Added trigger {id}!")).await?;
+    }
+
+    Ok(())
+}
+
+/// Stops a trigger from replying
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "remove",
+    required_permissions = "MANAGE_MESSAGES"
+)]
+async fn remove_trigger(
+    ctx: Context<'_>,
+    #[description = "The trigger"]
+    #[autocomplete = "autocomplete_trigger"]
+    id: i32,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild_id = ctx.guild_id().unwrap();
+
+    let removed = sqlx::query!(
+        "DELETE FROM tag_trigger WHERE tag_trigger.guild_id = $1 AND tag_trigger.id = $2",
+        guild_id.into_db(),
+        id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    if removed.rows_affected() == 0 {
+        ctx.say("There is no trigger with that ID!").await?;
+        return Ok(());
+    }
+
+    invalidate_tag_triggers(ctx.data(), guild_id);
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// Lists the triggers, the channels they reply in and the roles they ignore
+#[poise::command(slash_command, prefix_command, guild_only, rename = "list")]
+async fn list_triggers(ctx: Context<'_>) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild = ctx.guild_id().unwrap().into_db();
+
+    let triggers = sqlx::query!(
+        r#"SELECT id, command_name, kind as "kind: TriggerKind", pattern, cooldown_seconds
+        FROM tag_trigger WHERE guild_id = $1 ORDER BY id"#,
+        guild
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    let channels = sqlx::query_scalar!(
+        r#"SELECT channel_id as "channel_id: database::ChannelId" FROM tag_trigger_channel WHERE guild_id = $1"#,
+        guild
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    let roles = sqlx::query_scalar!(
+        r#"SELECT role_id as "role_id: database::RoleId" FROM tag_trigger_ignored_role WHERE guild_id = $1"#,
+        guild
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    let mut description = String::new();
+
+    for trigger in triggers {
+        writeln!(
+            &mut description,
+            "`{}` **{}** on {} `{}`, every {}s",
+            trigger.id,
+            trigger.command_name,
+            trigger.kind.name().to_lowercase(),
+            trigger.pattern.replace('`', "'"),
+            trigger.cooldown_seconds
+        )?;
+    }
+
+    if description.is_empty() {
+        description.push_str("No triggers have been added");
+    }
+
+    let mention_list = |mentions: Vec<String>| {
+        if mentions.is_empty() {
+            "None".to_string()
+        } else {
+            truncate(&mentions.join(", "), EMBED_FIELD_LENGTH)
+        }
+    };
+
+    let channels = channels
+        .into_iter()
+        .map(|channel| channel.into_serenity().mention().to_string())
+        .collect();
+
+    let roles = roles
+        .into_iter()
+        .map(|role| role.into_serenity().mention().to_string())
+        .collect();
+
+    // An embed so the roles do not get pinged
+    let embed = CreateEmbed::new()
+        .title("Tag triggers")
+        .description(truncate(&description, EMBED_DESCRIPTION_LENGTH))
+        .field("Replies in", mention_list(channels), false)
+        .field("Ignores", mention_list(roles), false)
+        .color(EMBED_COLOR);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Lets triggers reply in a channel, including its threads, or every channel of a category
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "channel",
+    required_permissions = "MANAGE_MESSAGES"
+)]
+async fn trigger_channel(
+    ctx: Context<'_>,
+    #[description = "The channel or category"] channel: Channel,
+    #[description = "Whether triggers reply in it"] enabled: bool,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild_id = ctx.guild_id().unwrap();
+
+    if enabled {
+        sqlx::query!(
+            "INSERT INTO tag_trigger_channel (guild_id, channel_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            guild_id.into_db(),
+            channel.id().into_db()
+        )
+        .execute(&ctx.data().db)
+        .await?;
+    } else {
+        sqlx::query!(
+            "DELETE FROM tag_trigger_channel WHERE guild_id = $1 AND channel_id = $2",
+            guild_id.into_db(),
+            channel.id().into_db()
+        )
+        .execute(&ctx.data().db)
+        .await?;
+    }
+
+    invalidate_tag_triggers(ctx.data(), guild_id);
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// Keeps members with a role from setting off triggers, such as the helpers answering questions
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "ignore-role",
+    required_permissions = "MANAGE_MESSAGES"
+)]
+async fn ignore_role(
+    ctx: Context<'_>,
+    #[description = "The role"] role: Role,
+    #[description = "Whether members with it are ignored"] ignored: bool,
+) -> Result<(), Error> {
+    // SAFETY: Since this command is guild_only this should NEVER fail
+    let guild_id = ctx.guild_id().unwrap();
+
+    if ignored {
+        sqlx::query!(
+            "INSERT INTO tag_trigger_ignored_role (guild_id, role_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            guild_id.into_db(),
+            role.id.into_db()
+        )
+        .execute(&ctx.data().db)
+        .await?;
+    } else {
+        sqlx::query!(
+            "DELETE FROM tag_trigger_ignored_role WHERE guild_id = $1 AND role_id = $2",
+            guild_id.into_db(),
+            role.id.into_db()
+        )
+        .execute(&ctx.data().db)
+        .await?;
+    }
+
+    invalidate_tag_triggers(ctx.data(), guild_id);
+
+    ctx.say("Done!").await?;
+
+    Ok(())
+}
+
+/// Suggests the server's triggers by their tag and pattern
+async fn autocomplete_trigger(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    let Some(guild) = ctx.guild_id() else {
+        return Vec::new();
+    };
+
+    let Ok(triggers) = sqlx::query!(
+        "SELECT id, command_name, pattern FROM tag_trigger
+        WHERE guild_id = $1 AND (command_name ILIKE '%' || $2 || '%' OR pattern ILIKE '%' || $2 || '%')
+        ORDER BY id LIMIT $3",
+        guild.into_db(),
+        escape_like(partial),
+        MAX_AUTOCOMPLETE_CHOICES
+    )
+    .fetch_all(&ctx.data().db)
+    .await
+    else {
+        return Vec::new();
+    };
+
+    triggers
+        .into_iter()
+        .map(|trigger| {
+            let label = format!(
+                "{}: {} (ID {})",
+                trigger.command_name, trigger.pattern, trigger.id
+            );
+
+            // Discord does not allow longer choice names
+            AutocompleteChoice::new(truncate(&label, CHOICE_NAME_LENGTH), trigger.id)
+        })
+        .collect()
+}
+
+/// Whether the author can change every tag, not only those they added
+async fn is_tag_moderator(ctx: Context<'_>) -> Result<bool, Error> {
+    let Some(member) = ctx.author_member().await else {
+        return Ok(false);
+    };
+
+    // Slash commands come with the author's permissions, prefix commands have to work them out
+    if let Some(permissions) = member.permissions {
+        return Ok(permissions.manage_messages());
+    }
+
+    let Some(channel) = ctx.guild_channel().await else {
+        return Ok(false);
+    };
+
+    let permissions = ctx
+        .guild()
+        .map(|guild| guild.user_permissions_in(&channel, &member));
+
+    Ok(permissions.is_some_and(|permissions| permissions.manage_messages()))
+}
+
+/// Whether the author can change the tag `name`, being a moderator or the member who owns it
+async fn can_edit_tag(ctx: Context<'_>, guild: i64, name: &str) -> Result<bool, Error> {
+    if is_tag_moderator(ctx).await? {
+        return Ok(true);
+    }
+
+    let owner = sqlx::query_scalar!(
+        "SELECT creator_id FROM tag WHERE tag.guild_id = $1 AND tag.command_name = $2 AND tag.member_owned",
+        guild,
+        name
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?
+    .flatten();
+
+    Ok(owner == Some(ctx.author().id.into_db()))
+}
+
+/// Saves the tag `name` as it is now as its newest revision, made by `author`.
+/// Runs in the transaction that changed the tag, so concurrent edits get their revisions one after the other.
+async fn record_tag_revision(
+    tx: &mut Transaction<'_, Postgres>,
+    author: UserId,
+    guild: i64,
+    name: &str,
+) -> Result<(), Error> {
+    let author = author.into_db();
+
+    sqlx::query!(
+        "SELECT 1 as locked FROM tag WHERE tag.guild_id = $1 AND tag.command_name = $2 FOR UPDATE",
+        guild,
+        name
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE tag SET last_editor_id = $3 WHERE tag.guild_id = $1 AND tag.command_name = $2",
+        guild,
+        name,
+        author
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    // Most edits keep the file, so the one already stored is reused instead of copying it again
+    let stored = sqlx::query_scalar!(
+        "SELECT tag_attachment.id FROM tag_attachment
+        JOIN tag ON tag.guild_id = tag_attachment.guild_id AND tag.command_name = tag_attachment.command_name
+            AND tag.attachment = tag_attachment.attachment
+        WHERE tag.guild_id = $1 AND tag.command_name = $2
+        LIMIT 1",
+        guild,
+        name
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let attachment = match stored {
+        Some(id) => Some(id),
+        None => {
+            sqlx::query_scalar!(
+                "INSERT INTO tag_attachment (guild_id, command_name, attachment)
+                SELECT guild_id, command_name, attachment FROM tag
+                WHERE tag.guild_id = $1 AND tag.command_name = $2 AND tag.attachment IS NOT NULL
+                RETURNING id",
+                guild,
+                name
+            )
+            .fetch_optional(&mut **tx)
+            .await?
+        }
+    };
+
+    sqlx::query!(
+        "INSERT INTO tag_revision (guild_id, command_name, revision, tag_description, content_type, embed_title, embed_color, embed_image,
+            attachment_name, attachment_id, editor_id)
+        SELECT guild_id, command_name,
+            COALESCE((SELECT MAX(revision) FROM tag_revision WHERE tag_revision.guild_id = tag.guild_id AND tag_revision.command_name = tag.command_name), 0) + 1,
+            tag_description, content_type, embed_title, embed_color, embed_image, attachment_name, $4, $3
+        FROM tag WHERE tag.guild_id = $1 AND tag.command_name = $2",
+        guild,
+        name,
+        author,
+        attachment
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Mentions a user, or says "someone" if who it was is not known
+fn someone(user: Option<database::UserId>) -> String {
+    user.map_or("someone".to_string(), |user| {
+        user.into_serenity().mention().to_string()
+    })
+}
+
+/// Whether `link` is an http or https URL, the only kind Discord shows images from
+fn is_web_link(link: &str) -> bool {
+    Url::parse(link).is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+}
+
+/// Reads a color written as hex, with or without a leading `#`
+fn parse_color(color: &str) -> Option<Color> {
+    let color = color.strip_prefix('#').unwrap_or(color);
+
+    match color.len() {
+        6 => u32::from_str_radix(color, 16).ok().map(Color::new),
+        _ => None,
+    }
+}
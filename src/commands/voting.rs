@@ -16,7 +16,7 @@ use crate::{Context, Error};
 #[poise::command(prefix_command, guild_only, aliases("b", "burg_vote"))]
 pub async fn burg_vote(ctx: Context<'_>, message: Message) -> Result<(), Error> {
     let mut users_votes =
-        HashMap::<UserId, Result<(ReactionType, Option<ReactionType>), User>>::new();
+        HashMap::<UserId, Result<(ReactionType, Option<ReactionType>), Box<User>>>::new();
 
     let mut reaction_users = user_reaction_stream(ctx, &message);
 
@@ -26,7 +26,7 @@ pub async fn burg_vote(ctx: Context<'_>, message: Message) -> Result<(), Error>
                 .entry(user.id)
                 .and_modify(|x| match x {
                     Ok((_, second @ None)) => *second = Some(reaction.clone()),
-                    ballot @ Ok((_, Some(_))) => *ballot = Err(user),
+                    ballot @ Ok((_, Some(_))) => *ballot = Err(Box::new(user)),
                     Err(_) => (),
                 })
                 .or_insert_with(|| Ok((reaction.clone(), None)));
@@ -44,7 +44,7 @@ pub async fn burg_vote(ctx: Context<'_>, message: Message) -> Result<(), Error>
                 *party_votes.entry(second).or_insert(0) += 1;
             }
             Ok((first, None)) => *party_votes.entry(first).or_insert(0) += 2,
-            Err(user) => multivoters.push(user),
+            Err(user) => multivoters.push(*user),
         }
     }
 
@@ -1,20 +1,40 @@
-use poise::serenity_prelude::GuildId;
+use dashmap::{DashMap, DashSet};
+use poise::serenity_prelude as serenity;
 use sqlx::{postgres::PgPoolOptions, Error, PgPool};
 use std::env;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use std::time::Instant;
 
 mod ids;
 pub mod starboard;
+pub mod tag_triggers;
 
 pub use ids::*;
 
 // Data shared across commands and events
+#[derive(Clone)]
 pub struct Data {
     pub db: PgPool,
+    /// Messages and emoji waiting for their starboard entries to be recounted
+    pub pending_starboard_updates: Arc<DashSet<(serenity::MessageId, String)>>,
+    /// The tag triggers of each guild that has had a message since they last changed
+    pub tag_triggers: Arc<DashMap<serenity::GuildId, Arc<tag_triggers::GuildTriggers>>>,
+    /// Goes up whenever any guild's tag triggers change, so triggers loaded while they changed are not cached
+    pub tag_trigger_generation: Arc<AtomicU64>,
+    /// Until when each tag trigger that replied lately is cooling down
+    pub tag_trigger_cooldowns: Arc<DashMap<i32, Instant>>,
 }
 
 impl Data {
     pub fn new(database: PgPool) -> Data {
-        Data { db: database }
+        Data {
+            db: database,
+            pending_starboard_updates: Arc::default(),
+            tag_triggers: Arc::default(),
+            tag_trigger_generation: Arc::default(),
+            tag_trigger_cooldowns: Arc::default(),
+        }
     }
 }
 
@@ -37,7 +57,7 @@ pub async fn init_data() -> Data {
 
 /// Creates a table for the provided guild ID. Errors if there is already a table present
 /// or if the database errors.
-pub async fn init_guild(data: &Data, guild_id: GuildId) -> Result<(), Error> {
+pub async fn init_guild(data: &Data, guild_id: serenity::GuildId) -> Result<(), Error> {
     sqlx::query!(
         "INSERT INTO guild (discord_id) VALUES ($1)",
         guild_id.into_db()
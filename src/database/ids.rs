@@ -38,3 +38,4 @@ database_id!(ChannelId);
 database_id!(MessageId);
 database_id!(GuildId);
 database_id!(UserId);
+database_id!(RoleId);
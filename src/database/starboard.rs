@@ -1,31 +1,721 @@
-use core::mem;
+use core::num::NonZeroU64;
+use core::time::Duration;
+use std::collections::{HashMap, HashSet};
 
 use crate::database::{self, IntoDatabase};
-use crate::{Data, Error, EMBED_COLOR};
+use crate::util::{MAX_CONTENT_LENGTH, SECONDS_PER_DAY};
+use crate::{Data, Error};
 use poise::serenity_prelude::{
-    ChannelId, Context, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateMessage,
-    EditMessage, GuildId, Message, MessageId, Reaction, User,
+    http::StatusCode, parse_message_url, Channel, ChannelId, ChannelType, Context, CreateEmbed,
+    CreateForumPost, CreateMessage, EditMessage, EditThread, Error as SerenityError, GuildChannel,
+    GuildId, Message, MessageId, Reaction, ReactionType, RoleId, Timestamp, User, UserId,
 };
+use serde::{Deserialize, Serialize};
 use sqlx::Error as SQLxError;
 
-pub async fn add_starboard_tables(
+mod render;
+pub mod transfer;
+
+/// The content template of new starboards
+pub const DEFAULT_CONTENT_TEMPLATE: &str = "{emoji} {count}";
+
+/// The longest a content template can be, leaving room for the emoji, count and channel that get filled in
+pub const MAX_TEMPLATE_LENGTH: usize = MAX_CONTENT_LENGTH / 2;
+
+/// How long to wait for more reactions on a message before recounting it
+const UPDATE_DELAY: Duration = Duration::from_secs(3);
+
+/// How long to wait between entries when reconciling, so start up leaves room in the rate limits for everything else
+const RECONCILE_DELAY: Duration = Duration::from_millis(250);
+
+/// The configuration of a single starboard
+pub struct Starboard {
+    pub id: i32,
+    pub guild_id: database::GuildId,
+    pub emoji: String,
+    pub starboard_channel: database::ChannelId,
+    pub min_reactions: i32,
+    /// Whether the author's own reaction counts
+    pub self_star: bool,
+    /// Whether reactions from bots are left out of the count
+    pub ignore_bots: bool,
+    /// Whether reactions on the starboard post count towards the original message
+    pub count_starboard_reactions: bool,
+    /// Whether posts keep showing the message as it was when starred, instead of following edits
+    pub freeze_content: bool,
+    /// Whether posts stay on this starboard after being promoted to a higher tier of the same emoji
+    pub keep_promoted: bool,
+    /// The content of posts, with `{emoji}`, `{count}` and `{channel}` filled in
+    pub content_template: String,
+    /// How old an account has to be for its reactions to count
+    pub min_account_days: i32,
+    /// How long someone has to have been in the server for their reactions to count
+    pub min_member_days: i32,
+    /// Whether posts stay after their original message is deleted
+    pub keep_history: bool,
+}
+
+/// Adds a new starboard, its `id` is left for the database to pick and returned
+pub async fn add_starboard_tables(data: &Data, starboard: &Starboard) -> Result<i32, SQLxError> {
+    let record = sqlx::query!(
+        r#"INSERT INTO starboard (guild_id, emoji, starboard_channel, min_reactions, self_star, ignore_bots, count_starboard_reactions, freeze_content, keep_promoted, content_template,
+            min_account_days, min_member_days, keep_history)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        RETURNING id"#,
+        starboard.guild_id.into_serenity().into_db(),
+        starboard.emoji,
+        starboard.starboard_channel.into_serenity().into_db(),
+        starboard.min_reactions,
+        starboard.self_star,
+        starboard.ignore_bots,
+        starboard.count_starboard_reactions,
+        starboard.freeze_content,
+        starboard.keep_promoted,
+        starboard.content_template,
+        starboard.min_account_days,
+        starboard.min_member_days,
+        starboard.keep_history
+    )
+    .fetch_one(&data.db)
+    .await?;
+
+    Ok(record.id)
+}
+
+/// Changes to make to an existing starboard, `None` keeps the current setting
+#[derive(Default)]
+pub struct StarboardEdit<'a> {
+    pub channel: Option<ChannelId>,
+    pub emoji: Option<&'a str>,
+    pub min_reactions: Option<i32>,
+    pub self_star: Option<bool>,
+    pub ignore_bots: Option<bool>,
+    pub count_starboard_reactions: Option<bool>,
+    pub freeze_content: Option<bool>,
+    pub keep_promoted: Option<bool>,
+    pub content_template: Option<&'a str>,
+    pub min_account_days: Option<i32>,
+    pub min_member_days: Option<i32>,
+    pub keep_history: Option<bool>,
+}
+
+/// Changes the settings of an existing starboard without losing its entries.
+/// Posts below the new minimum are removed unless they are locked, the rest are moved or edited to match. Returns `false` if the guild has no starboard `id`.
+pub async fn edit_starboard_tables(
+    ctx: &Context,
     data: &Data,
     guild_id: GuildId,
-    channel_id: ChannelId,
-    emoji: &str,
-    min_reactions: i32,
-) -> Result<(), SQLxError> {
+    id: i32,
+    changes: StarboardEdit<'_>,
+) -> Result<bool, Error> {
+    let mut tx = data.db.begin().await?;
+
+    let possible_starboard = sqlx::query!(
+        r#"SELECT emoji, starboard_channel as "starboard_channel: database::ChannelId", min_reactions, content_template FROM starboard
+                    WHERE starboard.guild_id = $1 AND starboard.id = $2 FOR UPDATE"#,
+        guild_id.into_db(),
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(old) = possible_starboard else {
+        return Ok(false);
+    };
+
+    let old_channel = old.starboard_channel.into_serenity();
+    let channel = changes.channel.unwrap_or(old_channel);
+    let new_emoji = changes.emoji.unwrap_or(&old.emoji);
+    let min_reactions = changes.min_reactions.unwrap_or(old.min_reactions);
+    let template = changes.content_template.unwrap_or(&old.content_template);
+
     sqlx::query!(
-        "INSERT INTO starboard (guild_id, emoji, starboard_channel, min_reactions) VALUES ($1, $2, $3, $4)",
+        r#"UPDATE starboard SET emoji = $2, starboard_channel = $3, min_reactions = $4, self_star = COALESCE($5, self_star),
+            ignore_bots = COALESCE($6, ignore_bots), count_starboard_reactions = COALESCE($7, count_starboard_reactions),
+            freeze_content = COALESCE($8, freeze_content), keep_promoted = COALESCE($9, keep_promoted), content_template = $10,
+            min_account_days = COALESCE($11, min_account_days), min_member_days = COALESCE($12, min_member_days),
+            keep_history = COALESCE($13, keep_history)
+        WHERE starboard.id = $1"#,
+        id,
+        new_emoji,
+        channel.into_db(),
+        min_reactions,
+        changes.self_star,
+        changes.ignore_bots,
+        changes.count_starboard_reactions,
+        changes.freeze_content,
+        changes.keep_promoted,
+        template,
+        changes.min_account_days,
+        changes.min_member_days,
+        changes.keep_history
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let below_minimum = sqlx::query!(
+        r#"DELETE FROM starboard_tracked WHERE starboard_tracked.starboard_id = $1 AND starboard_tracked.reaction_count < $2
+            AND starboard_tracked.message_id NOT IN (SELECT message_id FROM starboard_locked_message WHERE starboard_locked_message.guild_id = $3 AND NOT starboard_locked_message.removed)
+        RETURNING starboard_post_id as "starboard_post_id: database::MessageId""#,
+        id,
+        min_reactions,
+        guild_id.into_db()
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let remaining = sqlx::query!(
+        r#"SELECT message_id as "message_id: database::MessageId", starboard_post_id as "starboard_post_id: database::MessageId", reaction_count,
+            source_channel as "source_channel: database::ChannelId", source_deleted
+        FROM starboard_tracked WHERE starboard_tracked.starboard_id = $1"#,
+        id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    // Posts deleted by hand are already gone, which is what we want anyway
+    for record in below_minimum {
+        match delete_post(ctx, old_channel, record.starboard_post_id.into_serenity()).await {
+            Ok(()) => (),
+            Err(err) if is_not_found(&err) => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    if channel == old_channel && new_emoji == old.emoji && template == old.content_template {
+        return Ok(true);
+    }
+
+    let tiers = get_emoji_tiers(data, id).await?;
+
+    for entry in remaining {
+        let old_post = entry.starboard_post_id.into_serenity();
+
+        // If the post was deleted by hand there is nothing left to move or edit
+        let post = match fetch_post(ctx, old_channel, old_post).await {
+            Ok(post) => post,
+            Err(err) if is_not_found(&err) => {
+                delete_starboard_entry_row(data, old_post).await?;
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        // Older posts kept the link to the original message in their content
+        let link = match entry.source_channel {
+            _ if entry.source_deleted => None,
+            Some(source_channel) => Some(
+                entry
+                    .message_id
+                    .into_serenity()
+                    .link(source_channel.into_serenity(), Some(guild_id)),
+            ),
+            None => post
+                .content
+                .split(' ')
+                .find(|word| parse_message_url(word).is_some())
+                .map(str::to_string),
+        };
+
+        let source = match entry.source_channel {
+            Some(source_channel) => {
+                Some(get_source_channel(ctx, source_channel.into_serenity()).await)
+            }
+            None => None,
+        };
+
+        let emoji = render::tier_emoji(new_emoji, &tiers, entry.reaction_count);
+        let content = render::post_content(template, emoji, entry.reaction_count, source.as_ref());
+        let components = link.map(render::jump_components).unwrap_or_default();
+
+        if channel == old_channel {
+            let edit = EditMessage::new().content(content).components(components);
+            edit_post(ctx, old_channel, old_post, edit).await?;
+            continue;
+        }
+
+        let title = render::copied_post_title(&post);
+
+        let moved = CreateMessage::new()
+            .content(content)
+            .embeds(post.embeds.into_iter().map(Into::into).collect())
+            .components(components);
+
+        let moved = send_post(ctx, channel, moved, title, emoji, source.as_ref()).await?;
+
+        sqlx::query!(
+            "UPDATE starboard_tracked SET starboard_post_id = $1 WHERE starboard_tracked.starboard_post_id = $2",
+            moved.into_db(),
+            old_post.into_db()
+        )
+        .execute(&data.db)
+        .await?;
+
+        delete_post(ctx, old_channel, old_post).await?;
+    }
+
+    // The counts so far were of the old emoji
+    if new_emoji != old.emoji {
+        recount_starboard_entries(ctx, data, guild_id, id, new_emoji).await?;
+    }
+
+    Ok(true)
+}
+
+/// Recounts every entry of a starboard with its new `emoji`.
+/// Kept history of deleted messages can not be recounted, so it is removed instead.
+async fn recount_starboard_entries(
+    ctx: &Context,
+    data: &Data,
+    guild_id: GuildId,
+    id: i32,
+    emoji: &str,
+) -> Result<(), Error> {
+    let tiers = get_starboard_tiers(data, guild_id, emoji).await?;
+
+    let entries = sqlx::query_as!(
+        TrackedEntry,
+        r#"SELECT starboard_tracked.message_id as "message_id: database::MessageId", starboard.guild_id as "guild_id!: database::GuildId", starboard.emoji as "emoji!",
+            starboard.starboard_channel as "starboard_channel: database::ChannelId", starboard_tracked.starboard_post_id as "starboard_post_id: database::MessageId",
+            starboard_tracked.source_channel as "source_channel: database::ChannelId", starboard_tracked.source_deleted
+        FROM starboard_tracked
+        JOIN starboard ON starboard.id = starboard_tracked.starboard_id
+        WHERE starboard.id = $1"#,
+        id
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let mut recounted = HashSet::new();
+
+    for entry in entries {
+        if entry.source_deleted {
+            let post = entry.starboard_post_id.into_serenity();
+
+            match delete_post(ctx, entry.starboard_channel.into_serenity(), post).await {
+                Ok(()) => (),
+                Err(err) if is_not_found(&err) => (),
+                Err(err) => return Err(err.into()),
+            }
+
+            delete_starboard_entry_row(data, post).await?;
+            continue;
+        }
+
+        reconcile_starboard_entry(ctx, data, entry, &tiers, &mut recounted).await?;
+    }
+
+    Ok(())
+}
+
+/// Lists every starboard in the guild, with the tiers of each emoji from lowest to highest
+pub async fn get_starboards(data: &Data, guild_id: GuildId) -> Result<Vec<Starboard>, SQLxError> {
+    sqlx::query_as!(
+        Starboard,
+        r#"SELECT id, guild_id as "guild_id!: database::GuildId", emoji as "emoji!", starboard_channel as "starboard_channel: database::ChannelId", min_reactions,
+            self_star, ignore_bots, count_starboard_reactions, freeze_content, keep_promoted, content_template, min_account_days, min_member_days, keep_history
+        FROM starboard WHERE starboard.guild_id = $1
+        ORDER BY starboard.emoji, starboard.min_reactions"#,
+        guild_id.into_db()
+    )
+    .fetch_all(&data.db)
+    .await
+}
+
+/// Which channels feed a starboard, and whose reactions count towards it
+pub struct StarboardFilters {
+    /// If not empty, only these channels (or their categories and parents) feed the starboard
+    pub allowed_channels: Vec<ChannelId>,
+    pub denied_channels: Vec<ChannelId>,
+    /// If not empty, only reactions from members with one of these roles count
+    pub required_roles: Vec<RoleId>,
+}
+
+pub async fn get_starboard_filters(data: &Data, id: i32) -> Result<StarboardFilters, SQLxError> {
+    let channels = sqlx::query!(
+        r#"SELECT channel_id as "channel_id: database::ChannelId", allow FROM starboard_channel_filter
+                    WHERE starboard_channel_filter.starboard_id = $1"#,
+        id
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let required_roles = sqlx::query!(
+        r#"SELECT role_id as "role_id: database::RoleId" FROM starboard_role_filter
+                    WHERE starboard_role_filter.starboard_id = $1"#,
+        id
+    )
+    .fetch_all(&data.db)
+    .await?
+    .into_iter()
+    .map(|record| record.role_id.into_serenity())
+    .collect();
+
+    let (allowed, denied): (Vec<_>, Vec<_>) = channels.into_iter().partition(|record| record.allow);
+
+    Ok(StarboardFilters {
+        allowed_channels: allowed
+            .into_iter()
+            .map(|record| record.channel_id.into_serenity())
+            .collect(),
+        denied_channels: denied
+            .into_iter()
+            .map(|record| record.channel_id.into_serenity())
+            .collect(),
+        required_roles,
+    })
+}
+
+/// Adds `channel` to the allow or deny list of a starboard, replacing any previous entry.
+/// Returns `false` if the guild has no starboard `id`.
+pub async fn add_starboard_channel_filter(
+    data: &Data,
+    guild_id: GuildId,
+    id: i32,
+    channel: ChannelId,
+    allow: bool,
+) -> Result<bool, SQLxError> {
+    let result = sqlx::query!(
+        r#"INSERT INTO starboard_channel_filter (starboard_id, channel_id, allow)
+        SELECT id, $3, $4 FROM starboard WHERE starboard.guild_id = $1 AND starboard.id = $2
+        ON CONFLICT (starboard_id, channel_id) DO UPDATE SET allow = EXCLUDED.allow"#,
         guild_id.into_db(),
-        emoji,
-        channel_id.into_db(),
-        min_reactions
+        id,
+        channel.into_db(),
+        allow
     )
     .execute(&data.db)
     .await?;
 
-    Ok(())
+    Ok(result.rows_affected() > 0)
+}
+
+/// Removes `channel` from the allow and deny lists of a starboard. Returns `false` if it was in neither.
+pub async fn remove_starboard_channel_filter(
+    data: &Data,
+    guild_id: GuildId,
+    id: i32,
+    channel: ChannelId,
+) -> Result<bool, SQLxError> {
+    let result = sqlx::query!(
+        r#"DELETE FROM starboard_channel_filter USING starboard
+        WHERE starboard.id = starboard_channel_filter.starboard_id AND starboard.guild_id = $1 AND starboard.id = $2 AND starboard_channel_filter.channel_id = $3"#,
+        guild_id.into_db(),
+        id,
+        channel.into_db()
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Requires reactions to come from members with `role` (or any other required role) to count.
+/// Returns `false` if the guild has no starboard `id`.
+pub async fn add_starboard_role_filter(
+    data: &Data,
+    guild_id: GuildId,
+    id: i32,
+    role: RoleId,
+) -> Result<bool, SQLxError> {
+    let result = sqlx::query!(
+        r#"INSERT INTO starboard_role_filter (starboard_id, role_id)
+        SELECT id, $3 FROM starboard WHERE starboard.guild_id = $1 AND starboard.id = $2
+        ON CONFLICT DO NOTHING"#,
+        guild_id.into_db(),
+        id,
+        role.into_db()
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Stops requiring `role` for reactions to count. Returns `false` if it was not required.
+pub async fn remove_starboard_role_filter(
+    data: &Data,
+    guild_id: GuildId,
+    id: i32,
+    role: RoleId,
+) -> Result<bool, SQLxError> {
+    let result = sqlx::query!(
+        r#"DELETE FROM starboard_role_filter USING starboard
+        WHERE starboard.id = starboard_role_filter.starboard_id AND starboard.guild_id = $1 AND starboard.id = $2 AND starboard_role_filter.role_id = $3"#,
+        guild_id.into_db(),
+        id,
+        role.into_db()
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// A different emoji to show on posts once they reach `min_count` reactions
+#[derive(Serialize, Deserialize)]
+pub struct EmojiTier {
+    pub min_count: i32,
+    pub emoji: String,
+}
+
+/// The emoji tiers of a starboard, from lowest to highest
+pub async fn get_emoji_tiers(data: &Data, id: i32) -> Result<Vec<EmojiTier>, SQLxError> {
+    sqlx::query_as!(
+        EmojiTier,
+        "SELECT min_count, emoji FROM starboard_emoji_tier WHERE starboard_emoji_tier.starboard_id = $1 ORDER BY min_count",
+        id
+    )
+    .fetch_all(&data.db)
+    .await
+}
+
+/// Shows `emoji` on posts of a starboard from `min_count` reactions on, replacing any emoji already shown from there.
+/// Returns `false` if the guild has no starboard `id`.
+pub async fn add_starboard_emoji_tier(
+    data: &Data,
+    guild_id: GuildId,
+    id: i32,
+    min_count: i32,
+    emoji: &str,
+) -> Result<bool, SQLxError> {
+    let result = sqlx::query!(
+        r#"INSERT INTO starboard_emoji_tier (starboard_id, min_count, emoji)
+        SELECT id, $3, $4 FROM starboard WHERE starboard.guild_id = $1 AND starboard.id = $2
+        ON CONFLICT (starboard_id, min_count) DO UPDATE SET emoji = EXCLUDED.emoji"#,
+        guild_id.into_db(),
+        id,
+        min_count,
+        emoji
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Removes the emoji tier starting at `min_count`. Returns `false` if there was none.
+pub async fn remove_starboard_emoji_tier(
+    data: &Data,
+    guild_id: GuildId,
+    id: i32,
+    min_count: i32,
+) -> Result<bool, SQLxError> {
+    let result = sqlx::query!(
+        r#"DELETE FROM starboard_emoji_tier USING starboard
+        WHERE starboard.id = starboard_emoji_tier.starboard_id AND starboard.guild_id = $1 AND starboard.id = $2 AND starboard_emoji_tier.min_count = $3"#,
+        guild_id.into_db(),
+        id,
+        min_count
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// A starboard together with statistics about the posts it is tracking
+pub struct StarboardSummary {
+    pub id: i32,
+    pub emoji: String,
+    pub starboard_channel: database::ChannelId,
+    pub min_reactions: i32,
+    pub self_star: bool,
+    pub ignore_bots: bool,
+    pub count_starboard_reactions: bool,
+    pub freeze_content: bool,
+    pub keep_promoted: bool,
+    pub content_template: String,
+    pub min_account_days: i32,
+    pub min_member_days: i32,
+    pub keep_history: bool,
+    pub tracked_posts: i64,
+    pub top_post: Option<database::MessageId>,
+    pub top_reactions: Option<i32>,
+}
+
+/// Lists every starboard in the guild along with its tracked post count and most starred post
+pub async fn get_starboard_summaries(
+    data: &Data,
+    guild_id: GuildId,
+) -> Result<Vec<StarboardSummary>, SQLxError> {
+    sqlx::query_as!(
+        StarboardSummary,
+        r#"SELECT starboard.id, starboard.emoji as "emoji!", starboard.starboard_channel as "starboard_channel: database::ChannelId", starboard.min_reactions,
+            starboard.self_star, starboard.ignore_bots, starboard.count_starboard_reactions, starboard.freeze_content, starboard.keep_promoted,
+            starboard.content_template, starboard.min_account_days, starboard.min_member_days,
+            starboard.keep_history, (SELECT COUNT(*) FROM starboard_tracked WHERE starboard_tracked.starboard_id = starboard.id) as "tracked_posts!",
+            top.starboard_post_id as "top_post?: database::MessageId", top.reaction_count as "top_reactions?"
+        FROM starboard
+        LEFT JOIN LATERAL (
+            SELECT starboard_post_id, reaction_count FROM starboard_tracked
+                WHERE starboard_tracked.starboard_id = starboard.id
+                ORDER BY reaction_count DESC LIMIT 1
+        ) top ON true
+        WHERE starboard.guild_id = $1
+        ORDER BY starboard.emoji, starboard.min_reactions"#,
+        guild_id.into_db()
+    )
+    .fetch_all(&data.db)
+    .await
+}
+
+/// A message that made it onto one of the guild's starboards, at the highest tier it reached
+pub struct StarredMessage {
+    pub message_id: database::MessageId,
+    pub emoji: String,
+    pub starboard_channel: database::ChannelId,
+    pub starboard_post_id: database::MessageId,
+    pub source_channel: Option<database::ChannelId>,
+    pub author_id: Option<database::UserId>,
+    pub reaction_count: i32,
+    pub source_deleted: bool,
+}
+
+impl StarredMessage {
+    /// A link to the original message, or to the starboard post if the original is gone or we do not know where it is
+    pub async fn link(&self, ctx: &Context, guild_id: GuildId) -> String {
+        match self.source_channel.filter(|_| !self.source_deleted) {
+            Some(channel) => self
+                .message_id
+                .into_serenity()
+                .link(channel.into_serenity(), Some(guild_id)),
+            None => {
+                post_link(
+                    ctx,
+                    guild_id,
+                    self.starboard_channel.into_serenity(),
+                    self.starboard_post_id.into_serenity(),
+                )
+                .await
+            }
+        }
+    }
+}
+
+/// The most starred messages of the guild, optionally only those sent after `since` (in seconds since the Unix epoch)
+pub async fn get_top_starred(
+    data: &Data,
+    guild_id: GuildId,
+    since: Option<i64>,
+    limit: i64,
+) -> Result<Vec<StarredMessage>, SQLxError> {
+    // Message IDs start with the milliseconds since the Discord epoch, so they can be compared to a time directly
+    sqlx::query_as!(
+        StarredMessage,
+        r#"SELECT message_id as "message_id!: database::MessageId", emoji as "emoji!", starboard_channel as "starboard_channel!: database::ChannelId",
+            starboard_post_id as "starboard_post_id!: database::MessageId", source_channel as "source_channel: database::ChannelId",
+            author_id as "author_id: database::UserId", reaction_count as "reaction_count!",
+            source_deleted as "source_deleted!"
+        FROM starred_message
+        WHERE starred_message.guild_id = $1 AND ($2::BIGINT IS NULL OR starred_message.message_id >= ($2 * 1000 - 1420070400000) << 22)
+        ORDER BY starred_message.reaction_count DESC
+        LIMIT $3"#,
+        guild_id.into_db(),
+        since,
+        limit
+    )
+    .fetch_all(&data.db)
+    .await
+}
+
+/// A random message from any of the guild's starboards
+pub async fn get_random_starred(
+    data: &Data,
+    guild_id: GuildId,
+) -> Result<Option<StarredMessage>, SQLxError> {
+    sqlx::query_as!(
+        StarredMessage,
+        r#"SELECT message_id as "message_id!: database::MessageId", emoji as "emoji!", starboard_channel as "starboard_channel!: database::ChannelId",
+            starboard_post_id as "starboard_post_id!: database::MessageId", source_channel as "source_channel: database::ChannelId",
+            author_id as "author_id: database::UserId", reaction_count as "reaction_count!",
+            source_deleted as "source_deleted!"
+        FROM starred_message
+        WHERE starred_message.guild_id = $1
+        ORDER BY random()
+        LIMIT 1"#,
+        guild_id.into_db()
+    )
+    .fetch_optional(&data.db)
+    .await
+}
+
+/// How well a member has done on the guild's starboards
+pub struct UserStarStats {
+    pub total_stars: i64,
+    pub starred_messages: i64,
+    pub best: Option<StarredMessage>,
+}
+
+pub async fn get_user_star_stats(
+    data: &Data,
+    guild_id: GuildId,
+    user: UserId,
+) -> Result<UserStarStats, SQLxError> {
+    let totals = sqlx::query!(
+        r#"SELECT COALESCE(SUM(starred_message.reaction_count), 0) as "total_stars!", COUNT(*) as "starred_messages!"
+        FROM starred_message
+        WHERE starred_message.guild_id = $1 AND starred_message.author_id = $2"#,
+        guild_id.into_db(),
+        user.into_db()
+    )
+    .fetch_one(&data.db)
+    .await?;
+
+    let best = sqlx::query_as!(
+        StarredMessage,
+        r#"SELECT message_id as "message_id!: database::MessageId", emoji as "emoji!", starboard_channel as "starboard_channel!: database::ChannelId",
+            starboard_post_id as "starboard_post_id!: database::MessageId", source_channel as "source_channel: database::ChannelId",
+            author_id as "author_id: database::UserId", reaction_count as "reaction_count!",
+            source_deleted as "source_deleted!"
+        FROM starred_message
+        WHERE starred_message.guild_id = $1 AND starred_message.author_id = $2
+        ORDER BY starred_message.reaction_count DESC
+        LIMIT 1"#,
+        guild_id.into_db(),
+        user.into_db()
+    )
+    .fetch_optional(&data.db)
+    .await?;
+
+    Ok(UserStarStats {
+        total_stars: totals.total_stars,
+        starred_messages: totals.starred_messages,
+        best,
+    })
+}
+
+/// Recounts a message shortly after a change in its reactions, so a burst of reactions (or someone
+/// reacting and unreacting over and over) costs a single recount instead of one for every event
+pub fn queue_starboard_update(ctx: &Context, data: &Data, reaction: &Reaction) {
+    if reaction.guild_id.is_none() {
+        return;
+    }
+
+    let key = (reaction.message_id, reaction.emoji.to_string());
+
+    // A recount is already on its way and will see this change too
+    if !data.pending_starboard_updates.insert(key.clone()) {
+        return;
+    }
+
+    let (ctx, data, reaction) = (ctx.clone(), data.clone(), reaction.clone());
+
+    tokio::spawn(async move {
+        tokio::time::sleep(UPDATE_DELAY).await;
+
+        // Changes from here on need another recount, as this one might not see them
+        data.pending_starboard_updates.remove(&key);
+
+        if let Err(err) = manage_starboard_entry(&ctx, &data, &reaction).await {
+            tracing::error!(
+                "Failed to update starboard entry for message {}: {err}",
+                reaction.message_id
+            );
+        }
+    });
 }
 
 /// Manages the starboard response to a change in reactions
@@ -36,108 +726,693 @@ pub async fn manage_starboard_entry(
 ) -> Result<(), Error> {
     // Check if this reaction is in a guild, and get guild id
     let guild_id = match reaction.guild_id {
-        Some(id) => id.into_db(),
+        Some(id) => id,
         None => return Ok(()),
     };
 
     let emoji = reaction.emoji.clone();
     let emoji_string = emoji.to_string();
 
-    let possible_starboard = sqlx::query!(
-        r#"SELECT starboard_channel as "starboard_channel: database::ChannelId", min_reactions FROM starboard 
-                    WHERE starboard.guild_id = $1 AND starboard.emoji = $2"#,
-                    guild_id,
-        emoji_string
+    let tiers = get_starboard_tiers(data, guild_id, &emoji_string).await?;
+
+    // Return if we don't have a starboard for this emoji
+    if tiers.is_empty() {
+        return Ok(());
+    }
+
+    // Reactions on a starboard post can only ever count towards the original message
+    let message = match get_starboard_post_origin(data, reaction.message_id).await? {
+        Some(origin) => {
+            if origin.emoji != emoji_string
+                || !tiers.iter().any(|tier| tier.count_starboard_reactions)
+            {
+                return Ok(());
+            }
+
+            let Some(source_channel) = origin.source_channel else {
+                return Ok(());
+            };
+
+            source_channel
+                .into_serenity()
+                .message(ctx, origin.message_id.into_serenity())
+                .await?
+        }
+        None => reaction.message(ctx).await?,
+    };
+
+    update_starboard_entry(ctx, data, message, emoji, &tiers).await?;
+
+    Ok(())
+}
+
+/// Every starboard of the guild using `emoji`, from the lowest tier to the highest
+async fn get_starboard_tiers(
+    data: &Data,
+    guild_id: GuildId,
+    emoji: &str,
+) -> Result<Vec<Starboard>, SQLxError> {
+    sqlx::query_as!(
+        Starboard,
+        r#"SELECT id, guild_id as "guild_id!: database::GuildId", emoji as "emoji!", starboard_channel as "starboard_channel: database::ChannelId", min_reactions,
+            self_star, ignore_bots, count_starboard_reactions, freeze_content, keep_promoted, content_template, min_account_days, min_member_days, keep_history
+        FROM starboard WHERE starboard.guild_id = $1 AND starboard.emoji = $2
+        ORDER BY starboard.min_reactions"#,
+        guild_id.into_db(),
+        emoji
     )
-    .fetch_optional(&data.db)
+    .fetch_all(&data.db)
+    .await
+}
+
+/// Counts the `emoji` reactions on a message following the rules of each tier, and adds, edits or removes its entries to match.
+/// A message is promoted to the highest tier it reaches, lower tiers only keep it if they are set to.
+/// Returns whether the message is on any of the tiers afterwards.
+pub async fn update_starboard_entry(
+    ctx: &Context,
+    data: &Data,
+    message: Message,
+    emoji: ReactionType,
+    tiers: &[Starboard],
+) -> Result<bool, Error> {
+    let Some(guild_id) = tiers
+        .first()
+        .map(|starboard| starboard.guild_id.into_serenity())
+    else {
+        return Ok(false);
+    };
+
+    // Starboard posts are never starred themselves
+    if get_starboard_post_origin(data, message.id).await?.is_some() {
+        return Ok(false);
+    }
+
+    match get_moderation_state(data, guild_id, &message).await? {
+        ModerationState::Open => (),
+        ModerationState::Locked => return is_on_any_starboard(data, message.id, tiers).await,
+        ModerationState::Removed => {
+            for starboard in tiers {
+                remove_starboard_entry_from(ctx, data, message.id, starboard).await?;
+            }
+
+            return Ok(false);
+        }
+    }
+
+    let mut counts = Vec::with_capacity(tiers.len());
+    let mut tier_filters = Vec::with_capacity(tiers.len());
+
+    for starboard in tiers {
+        let filters = get_starboard_filters(data, starboard.id).await?;
+
+        let length = match is_channel_allowed(ctx, starboard, &filters, message.channel_id).await? {
+            true => {
+                count_reactions(ctx, data, &message, emoji.clone(), starboard, &filters).await?
+            }
+            false => 0,
+        };
+
+        counts.push(length);
+        tier_filters.push(filters);
+    }
+
+    let reached = |starboard: &Starboard, length: u64| length >= starboard.min_reactions as u64;
+
+    let highest = tiers
+        .iter()
+        .zip(&counts)
+        .rposition(|(starboard, &length)| reached(starboard, length));
+
+    for (i, ((starboard, length), filters)) in
+        tiers.iter().zip(counts).zip(tier_filters).enumerate()
+    {
+        let on_tier = match highest {
+            Some(highest) => {
+                i == highest
+                    || (i < highest && starboard.keep_promoted && reached(starboard, length))
+            }
+            None => false,
+        };
+
+        if !on_tier {
+            remove_starboard_entry_from(ctx, data, message.id, starboard).await?;
+            continue;
+        }
+
+        let added =
+            add_or_edit_starboard_entry(ctx, data, &message, length as usize, starboard).await?;
+
+        if added {
+            let reactors =
+                counted_reactors(ctx, data, &message, &emoji, starboard, &filters).await?;
+
+            record_starboard_audit(data, starboard, message.id, &reactors).await?;
+        }
+    }
+
+    Ok(highest.is_some())
+}
+
+/// What mods have decided about a message
+enum ModerationState {
+    /// The message goes on the starboards as usual
+    Open,
+    /// The entries of the message stay exactly as they are
+    Locked,
+    /// The message can never be on the starboards, because it was removed or its author is blacklisted
+    Removed,
+}
+
+async fn get_moderation_state(
+    data: &Data,
+    guild_id: GuildId,
+    message: &Message,
+) -> Result<ModerationState, SQLxError> {
+    let record = sqlx::query!(
+        r#"SELECT
+            (SELECT removed FROM starboard_locked_message WHERE starboard_locked_message.guild_id = $1 AND starboard_locked_message.message_id = $2) as "removed?",
+            EXISTS (SELECT 1 FROM starboard_blacklist WHERE starboard_blacklist.guild_id = $1 AND starboard_blacklist.user_id = $3) as "blacklisted!""#,
+        guild_id.into_db(),
+        message.id.into_db(),
+        message.author.id.into_db()
+    )
+    .fetch_one(&data.db)
     .await?;
 
-    // Return if we don't have a starboard for this emoji
-    let (starboard_channel, min_reactions) = match possible_starboard {
-        Some(record) => (
-            record.starboard_channel.into_serenity(),
-            record.min_reactions,
-        ),
-        None => return Ok(()),
+    Ok(match (record.removed, record.blacklisted) {
+        (Some(true), _) | (_, true) => ModerationState::Removed,
+        (Some(false), false) => ModerationState::Locked,
+        (None, false) => ModerationState::Open,
+    })
+}
+
+/// Whether a message has an entry on any of `starboards`
+pub async fn is_on_any_starboard(
+    data: &Data,
+    message: MessageId,
+    starboards: &[Starboard],
+) -> Result<bool, Error> {
+    let ids: Vec<i32> = starboards.iter().map(|starboard| starboard.id).collect();
+
+    let record = sqlx::query!(
+        r#"SELECT EXISTS (SELECT 1 FROM starboard_tracked WHERE starboard_tracked.message_id = $1 AND starboard_tracked.starboard_id = ANY($2)) as "exists!""#,
+        message.into_db(),
+        &ids
+    )
+    .fetch_one(&data.db)
+    .await?;
+
+    Ok(record.exists)
+}
+
+/// Locks the entries of a message in place, so they no longer follow its reactions or edits.
+/// With `removed` they are taken off the starboards instead, and the message can not come back.
+pub async fn lock_starboard_message(
+    ctx: &Context,
+    data: &Data,
+    guild_id: GuildId,
+    message: MessageId,
+    removed: bool,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"INSERT INTO starboard_locked_message (guild_id, message_id, removed) VALUES ($1, $2, $3)
+        ON CONFLICT (guild_id, message_id) DO UPDATE SET removed = EXCLUDED.removed"#,
+        guild_id.into_db(),
+        message.into_db(),
+        removed
+    )
+    .execute(&data.db)
+    .await?;
+
+    if removed {
+        remove_starboard_entry(ctx, data, &message).await?;
+    }
+
+    Ok(())
+}
+
+/// Lets a locked or removed message back onto the starboards from its next reaction on.
+/// Returns `false` if it was neither.
+pub async fn unlock_starboard_message(
+    data: &Data,
+    guild_id: GuildId,
+    message: MessageId,
+) -> Result<bool, SQLxError> {
+    let result = sqlx::query!(
+        "DELETE FROM starboard_locked_message WHERE starboard_locked_message.guild_id = $1 AND starboard_locked_message.message_id = $2",
+        guild_id.into_db(),
+        message.into_db()
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Keeps the messages of `user` off the guild's starboards, removing the ones already on them.
+/// Returns `false` if the user was already blacklisted.
+pub async fn add_starboard_blacklist(
+    ctx: &Context,
+    data: &Data,
+    guild_id: GuildId,
+    user: UserId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        "INSERT INTO starboard_blacklist (guild_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        guild_id.into_db(),
+        user.into_db()
+    )
+    .execute(&data.db)
+    .await?;
+
+    let entries = sqlx::query!(
+        r#"SELECT DISTINCT starboard_tracked.message_id as "message_id: database::MessageId"
+        FROM starboard_tracked
+        JOIN starboard ON starboard.id = starboard_tracked.starboard_id
+        WHERE starboard.guild_id = $1 AND starboard_tracked.author_id = $2"#,
+        guild_id.into_db(),
+        user.into_db()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    for entry in entries {
+        remove_starboard_entry(ctx, data, &entry.message_id.into_serenity()).await?;
+    }
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Lets the messages of `user` be starred again. Returns `false` if they were not blacklisted.
+pub async fn remove_starboard_blacklist(
+    data: &Data,
+    guild_id: GuildId,
+    user: UserId,
+) -> Result<bool, SQLxError> {
+    let result = sqlx::query!(
+        "DELETE FROM starboard_blacklist WHERE starboard_blacklist.guild_id = $1 AND starboard_blacklist.user_id = $2",
+        guild_id.into_db(),
+        user.into_db()
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Every user whose messages can not be starred in the guild
+pub async fn get_starboard_blacklist(
+    data: &Data,
+    guild_id: GuildId,
+) -> Result<Vec<UserId>, SQLxError> {
+    let records = sqlx::query!(
+        r#"SELECT user_id as "user_id: database::UserId" FROM starboard_blacklist WHERE starboard_blacklist.guild_id = $1"#,
+        guild_id.into_db()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| record.user_id.into_serenity())
+        .collect())
+}
+
+/// The original message a starboard post was made for
+struct StarboardPostOrigin {
+    message_id: database::MessageId,
+    emoji: String,
+    source_channel: Option<database::ChannelId>,
+}
+
+/// The original message of `message` if it is a starboard post, otherwise `message` itself
+pub async fn original_message_id(data: &Data, message: MessageId) -> Result<MessageId, SQLxError> {
+    Ok(get_starboard_post_origin(data, message)
+        .await?
+        .map_or(message, |origin| origin.message_id.into_serenity()))
+}
+
+/// Looks up the original message of `post`, if it is a starboard post
+async fn get_starboard_post_origin(
+    data: &Data,
+    post: MessageId,
+) -> Result<Option<StarboardPostOrigin>, SQLxError> {
+    sqlx::query_as!(
+        StarboardPostOrigin,
+        r#"SELECT starboard_tracked.message_id as "message_id: database::MessageId", starboard.emoji as "emoji!",
+            starboard_tracked.source_channel as "source_channel: database::ChannelId"
+        FROM starboard_tracked
+        JOIN starboard ON starboard.id = starboard_tracked.starboard_id
+        WHERE starboard_tracked.starboard_post_id = $1"#,
+        post.into_db()
+    )
+    .fetch_optional(&data.db)
+    .await
+}
+
+/// Checks if messages in `channel` can make it onto `starboard`.
+/// NSFW channels are left out unless the starboard is NSFW as well or they are explicitly allowed.
+async fn is_channel_allowed(
+    ctx: &Context,
+    starboard: &Starboard,
+    filters: &StarboardFilters,
+    channel: ChannelId,
+) -> Result<bool, Error> {
+    let Some(source) = channel.to_channel(ctx).await?.guild() else {
+        return Ok(false);
+    };
+
+    // Threads are filtered by their parent channel and its category, channels by their category
+    let category = match (&source.thread_metadata, source.parent_id) {
+        (Some(_), Some(parent))
+            if !filters.allowed_channels.is_empty() || !filters.denied_channels.is_empty() =>
+        {
+            parent
+                .to_channel(ctx)
+                .await?
+                .guild()
+                .and_then(|parent| parent.parent_id)
+        }
+        _ => None,
     };
 
-    let message = reaction.message(ctx).await?;
+    let ids = [Some(source.id), source.parent_id, category];
+    let listed = |list: &[ChannelId]| ids.iter().flatten().any(|id| list.contains(id));
 
-    let reactions = message
-        .reaction_users(ctx, emoji, Some(100), None)
-        .await
-        .unwrap_or(vec![]);
+    if listed(&filters.denied_channels) {
+        return Ok(false);
+    }
+
+    if listed(&filters.allowed_channels) {
+        return Ok(true);
+    }
+
+    if !filters.allowed_channels.is_empty() {
+        return Ok(false);
+    }
+
+    if !is_nsfw(ctx, &source).await? {
+        return Ok(true);
+    }
+
+    let starboard_channel = starboard
+        .starboard_channel
+        .into_serenity()
+        .to_channel(ctx)
+        .await?;
+
+    Ok(starboard_channel.guild().is_some_and(|c| c.nsfw))
+}
+
+/// Threads do not have their own NSFW setting, so they take it from their parent
+async fn is_nsfw(ctx: &Context, channel: &GuildChannel) -> Result<bool, SerenityError> {
+    match (&channel.thread_metadata, channel.parent_id) {
+        (Some(_), Some(parent)) => Ok(parent
+            .to_channel(ctx)
+            .await?
+            .guild()
+            .is_some_and(|parent| parent.nsfw)),
+        _ => Ok(channel.nsfw),
+    }
+}
+
+/// Counts the `emoji` reactions on a message that go towards `starboard`
+async fn count_reactions(
+    ctx: &Context,
+    data: &Data,
+    message: &Message,
+    emoji: ReactionType,
+    starboard: &Starboard,
+    filters: &StarboardFilters,
+) -> Result<u64, Error> {
+    if !starboard.ignore_bots
+        && !starboard.count_starboard_reactions
+        && filters.required_roles.is_empty()
+        && starboard.min_account_days == 0
+        && starboard.min_member_days == 0
+    {
+        let total = reaction_total(message, &emoji);
+
+        if starboard.self_star || total == 0 {
+            return Ok(total);
+        }
+
+        // Only the author's reaction has to be looked up, everyone else is already part of the count
+        return match has_reacted(ctx, message, emoji, message.author.id).await? {
+            true => Ok(total - 1),
+            false => Ok(total),
+        };
+    }
+
+    let reactors = counted_reactors(ctx, data, message, &emoji, starboard, filters).await?;
+
+    Ok(reactors.len() as u64)
+}
+
+/// How many `emoji` reactions a message has, according to the message itself
+fn reaction_total(message: &Message, emoji: &ReactionType) -> u64 {
+    let emoji_string = emoji.to_string();
+
+    message
+        .reactions
+        .iter()
+        .find(|r| r.reaction_type.to_string() == emoji_string)
+        .map_or(0, |r| r.count)
+}
+
+/// Fetches every user whose `emoji` reaction on a message goes towards `starboard`
+async fn counted_reactors(
+    ctx: &Context,
+    data: &Data,
+    message: &Message,
+    emoji: &ReactionType,
+    starboard: &Starboard,
+    filters: &StarboardFilters,
+) -> Result<Vec<User>, Error> {
+    let mut reactors = match reaction_total(message, emoji) {
+        0 => Vec::new(),
+        _ => all_reactors(ctx, message.channel_id, message.id, emoji).await?,
+    };
+
+    if starboard.count_starboard_reactions {
+        // Posts on every tier of the emoji count, so reactions are not lost when a message is promoted
+        let posts = sqlx::query!(
+            r#"SELECT starboard.starboard_channel as "starboard_channel: database::ChannelId", starboard_tracked.starboard_post_id as "starboard_post_id: database::MessageId"
+            FROM starboard_tracked
+            JOIN starboard ON starboard.id = starboard_tracked.starboard_id
+            WHERE starboard_tracked.message_id = $1 AND starboard.guild_id = $2 AND starboard.emoji = $3"#,
+            message.id.into_db(),
+            starboard.guild_id.into_serenity().into_db(),
+            emoji.to_string()
+        )
+        .fetch_all(&data.db)
+        .await?;
+
+        for post in posts {
+            let starboard_channel = post.starboard_channel.into_serenity();
+            let post = post.starboard_post_id.into_serenity();
+            let channel = post_channel(ctx, starboard_channel, post).await;
+
+            match all_reactors(ctx, channel, post, emoji).await {
+                Ok(post_reactors) => reactors.extend(post_reactors),
+                Err(err) if is_not_found(&err) => (),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    let now = Timestamp::now().unix_timestamp();
+    let min_account_age = i64::from(starboard.min_account_days) * SECONDS_PER_DAY;
+
+    reactors.retain(|reactor| {
+        (starboard.self_star || reactor.id != message.author.id)
+            && !(starboard.ignore_bots && reactor.bot)
+            && now - reactor.id.created_at().unix_timestamp() >= min_account_age
+    });
+
+    // Someone who reacted to both the original and the starboard post only counts once
+    reactors.sort_unstable_by_key(|reactor| reactor.id);
+    reactors.dedup_by_key(|reactor| reactor.id);
+
+    if filters.required_roles.is_empty() && starboard.min_member_days == 0 {
+        return Ok(reactors);
+    }
+
+    let guild_id = starboard.guild_id.into_serenity();
+    let min_member_age = i64::from(starboard.min_member_days) * SECONDS_PER_DAY;
+    let mut counted = Vec::with_capacity(reactors.len());
+
+    for reactor in reactors {
+        // Members are usually cached, so this rarely needs a request
+        let member = match guild_id.member(ctx, reactor.id).await {
+            Ok(member) => member,
+            Err(err) if is_not_found(&err) => continue,
+            Err(err) => return Err(err.into()),
+        };
+
+        let has_role = filters.required_roles.is_empty()
+            || member
+                .roles
+                .iter()
+                .any(|role| filters.required_roles.contains(role));
+
+        let old_enough = member
+            .joined_at
+            .is_some_and(|joined| now - joined.unix_timestamp() >= min_member_age);
+
+        if has_role && old_enough {
+            counted.push(reactor);
+        }
+    }
+
+    Ok(counted)
+}
+
+/// Remembers `reactors` as the users that got a message onto `starboard`, replacing whoever did so before
+async fn record_starboard_audit(
+    data: &Data,
+    starboard: &Starboard,
+    message: MessageId,
+    reactors: &[User],
+) -> Result<(), SQLxError> {
+    let mut tx = data.db.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM starboard_audit WHERE starboard_audit.starboard_id = $1 AND starboard_audit.message_id = $2",
+        starboard.id,
+        message.into_db()
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let users: Vec<i64> = reactors
+        .iter()
+        .map(|reactor| reactor.id.into_db())
+        .collect();
+
+    sqlx::query!(
+        r#"INSERT INTO starboard_audit (starboard_id, message_id, user_id)
+        SELECT $1, $2, * FROM UNNEST($3::BIGINT[])"#,
+        starboard.id,
+        message.into_db(),
+        &users
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// The users that got a message onto one of the guild's starboards
+pub struct StarboardAudit {
+    pub emoji: String,
+    pub starboard_channel: database::ChannelId,
+    pub users: Vec<database::UserId>,
+}
+
+/// Looks up who got `message` onto each of the guild's starboards it is or was on
+pub async fn get_starboard_audit(
+    data: &Data,
+    guild_id: GuildId,
+    message: MessageId,
+) -> Result<Vec<StarboardAudit>, SQLxError> {
+    sqlx::query_as!(
+        StarboardAudit,
+        r#"SELECT starboard.emoji, starboard.starboard_channel as "starboard_channel: database::ChannelId", array_agg(starboard_audit.user_id) as "users!: Vec<database::UserId>"
+        FROM starboard_audit
+        JOIN starboard ON starboard.id = starboard_audit.starboard_id
+        WHERE starboard.guild_id = $1 AND starboard_audit.message_id = $2
+        GROUP BY starboard.id
+        ORDER BY starboard.emoji, starboard.min_reactions"#,
+        guild_id.into_db(),
+        message.into_db()
+    )
+    .fetch_all(&data.db)
+    .await
+}
+
+/// Fetches every user that reacted to a message with `emoji`
+async fn all_reactors(
+    ctx: &Context,
+    channel: ChannelId,
+    message: MessageId,
+    emoji: &ReactionType,
+) -> Result<Vec<User>, SerenityError> {
+    const PAGE_SIZE: u8 = 100;
+
+    let mut reactors: Vec<User> = Vec::new();
+
+    loop {
+        let after = reactors.last().map(|reactor| reactor.id);
+
+        let page = channel
+            .reaction_users(ctx, message, emoji.clone(), Some(PAGE_SIZE), after)
+            .await?;
+
+        let last_page = page.len() < PAGE_SIZE.into();
+
+        reactors.extend(page);
+
+        if last_page {
+            return Ok(reactors);
+        }
+    }
+}
 
-    let length = {
-        if reactions.contains(&message.author) {
-            reactions.len() - 1
-        } else {
-            reactions.len()
-        }
-    };
+/// Checks if `user` is one of the users that reacted to `message` with `emoji`
+async fn has_reacted(
+    ctx: &Context,
+    message: &Message,
+    emoji: ReactionType,
+    user: UserId,
+) -> Result<bool, SerenityError> {
+    // Reactors are sorted by ID, so the first one after `user - 1` is `user` if they reacted
+    let after = NonZeroU64::new(user.get() - 1).map(UserId::from);
 
-    if length >= min_reactions.try_into().unwrap() {
-        add_or_edit_starboard_entry(
-            ctx,
-            data,
-            message,
-            &reactions,
-            emoji_string.as_str(),
-            starboard_channel,
-        )
-        .await?;
-    } else {
-        remove_starboard_entry_with_channel(ctx, data, message.id, starboard_channel).await?;
-    }
+    let reactors = message.reaction_users(ctx, emoji, Some(1), after).await?;
 
-    Ok(())
+    Ok(reactors.first().is_some_and(|reactor| reactor.id == user))
 }
 
-/// Edits a starboard entry, or creates one if one does not exist
+/// Edits a starboard entry, or creates one if one does not exist. Returns whether it was created.
 async fn add_or_edit_starboard_entry(
     ctx: &Context,
     data: &Data,
-    message: Message,
-    reactions: &[User],
-    emoji_string: &str,
-    channel: ChannelId,
-) -> Result<(), Error> {
+    message: &Message,
+    reactions: usize,
+    starboard: &Starboard,
+) -> Result<bool, Error> {
     let possible_entry = sqlx::query!(
-        r#"SELECT starboard_post_id as "id: database::MessageId", starboard_channel as "channel: database::ChannelId" FROM starboard_tracked 
-                    WHERE starboard_tracked.message_id = $1 AND starboard_tracked.emoji = $2"#,
+        r#"SELECT starboard_post_id as "id: database::MessageId" FROM starboard_tracked
+                    WHERE starboard_tracked.message_id = $1 AND starboard_tracked.starboard_id = $2"#,
         message.id.into_db(),
-        emoji_string
+        starboard.id
     )
     .fetch_optional(&data.db)
     .await?;
 
-    match possible_entry {
-        Some(post) => {
-            edit_starboard_entry(
-                ctx,
-                data,
-                post.id.into_serenity(),
-                post.channel.into_serenity(),
-                reactions.len(),
-                emoji_string,
-            )
-            .await?
-        }
-        None => {
-            add_starboard_entry(ctx, data, message, channel, emoji_string, reactions.len()).await?
+    if let Some(post) = possible_entry {
+        let post = post.id.into_serenity();
+
+        if edit_starboard_entry(ctx, data, post, message, reactions, starboard).await? {
+            return Ok(false);
         }
+
+        // The post was deleted without the bot noticing, so it is posted again like one deleted by hand
+        delete_starboard_entry_row(data, post).await?;
     }
 
-    Ok(())
+    add_starboard_entry(ctx, data, message, starboard, reactions).await?;
+
+    Ok(true)
 }
 
 /// Creates a new starboard entry
 async fn add_starboard_entry(
     ctx: &Context,
     data: &Data,
-    mut message: Message,
-    starboard_channel: ChannelId,
-    emoji_string: &str,
+    message: &Message,
+    starboard: &Starboard,
     current_reactions: usize,
 ) -> Result<(), Error> {
     let mut tx = data.db.begin().await?;
@@ -145,73 +1420,45 @@ async fn add_starboard_entry(
     // Add entry with temporary ID only for this transaction
     sqlx::query!(
         r#"INSERT INTO starboard_tracked 
-        (message_id, emoji, starboard_channel, starboard_post_id, reaction_count) VALUES ($1, $2, $3, 666, $4)"#,
+        (message_id, starboard_id, starboard_post_id, reaction_count, source_channel, author_id) VALUES ($1, $2, 666, $3, $4, $5)"#,
         message.id.into_db(),
-        emoji_string,
-        starboard_channel.into_db(),
-        current_reactions as i32
+        starboard.id,
+        current_reactions as i32,
+        message.channel_id.into_db(),
+        message.author.id.into_db()
     ).execute(&mut *tx)
     .await?;
 
-    let post = {
-        let mut attachments = mem::take(&mut message.attachments);
-
-        attachments.retain(|a| {
-            a.content_type
-                .as_deref()
-                .is_some_and(|t| t.starts_with("image/"))
-        });
-
-        let mut main_embed = CreateEmbed::new()
-            .author(
-                CreateEmbedAuthor::new(message.author.name.clone()).icon_url(message.author.face()),
-            )
-            .url("http://example.com/0")
-            .description(message.content.clone())
-            .color(EMBED_COLOR);
-
-        if let Some(message) = &message.referenced_message {
-            main_embed = main_embed.field("Replied Message:", &message.content, false);
-        }
-
-        let mut extra_embeds = Vec::new();
+    let source = get_source_channel(ctx, message.channel_id).await;
+    let content = render_post_content(data, starboard, current_reactions as i32, &source).await?;
+    let color = render::post_color(current_reactions as i32, starboard.min_reactions);
 
-        if let Some((first, extra)) = attachments.split_first() {
-            main_embed = main_embed.image(first.url.as_str());
+    // Messages fetched over REST have no guild, which their link needs
+    let guild_id = starboard.guild_id.into_serenity();
+    let link = message.id.link(message.channel_id, Some(guild_id));
 
-            let mut i = 1;
-            for attachment in extra[..extra.len().min(10)].iter() {
-                let embed = CreateEmbed::new()
-                    .color(EMBED_COLOR)
-                    .url(format!("http://example.com/{}", i / 4))
-                    .image(attachment.url.as_str());
+    let post = CreateMessage::new()
+        .content(content)
+        .embeds(render::post_embeds(message, guild_id, color, &source))
+        .components(render::jump_components(link));
 
-                extra_embeds.push(embed);
-                i += 1;
-            }
-        }
-
-        let last = extra_embeds.last_mut().unwrap_or(&mut main_embed);
-        *last = mem::take(last)
-            .footer(CreateEmbedFooter::new(message.id.to_string()))
-            .timestamp(message.timestamp);
-
-        CreateMessage::new()
-            .content(format!(
-                "{} | {emoji_string} {current_reactions}",
-                message.link()
-            ))
-            .add_embed(main_embed)
-            .add_embeds(extra_embeds)
-    };
+    let title = render::forum_post_title(&message.author.name, &message.content);
 
-    let post = starboard_channel.send_message(ctx, post).await?;
+    let post = send_post(
+        ctx,
+        starboard.starboard_channel.into_serenity(),
+        post,
+        title,
+        &starboard.emoji,
+        Some(&source),
+    )
+    .await?;
 
     sqlx::query!(
-        r#"UPDATE starboard_tracked SET starboard_post_id = $1 WHERE message_id = $2 AND emoji = $3"#,
-        post.id.into_db(),
+        r#"UPDATE starboard_tracked SET starboard_post_id = $1 WHERE message_id = $2 AND starboard_id = $3"#,
+        post.into_db(),
         message.id.into_db(),
-        emoji_string,
+        starboard.id,
     ).execute(&mut *tx)
     .await?;
 
@@ -220,53 +1467,151 @@ async fn add_starboard_entry(
     Ok(())
 }
 
-/// Edits an existing starboard entry
-async fn edit_starboard_entry(
+/// Regenerates the starboard posts of an edited message so they match it again.
+/// Posts on starboards that freeze their content and posts of locked messages are left alone.
+pub async fn refresh_starboard_posts(
     ctx: &Context,
     data: &Data,
-    message: MessageId,
     channel: ChannelId,
-    reactions: usize,
-    emoji_string: &str,
+    message: MessageId,
 ) -> Result<(), Error> {
-    let mut post = channel.message(ctx, message).await?;
+    let posts = sqlx::query!(
+        r#"SELECT starboard.starboard_channel as "starboard_channel: database::ChannelId", starboard_tracked.starboard_post_id as "starboard_post_id: database::MessageId",
+            starboard_tracked.reaction_count, starboard.min_reactions, starboard.guild_id as "guild_id!: database::GuildId"
+        FROM starboard_tracked
+        JOIN starboard ON starboard.id = starboard_tracked.starboard_id
+        WHERE starboard_tracked.message_id = $1 AND NOT starboard.freeze_content
+            AND NOT EXISTS (SELECT 1 FROM starboard_locked_message
+                WHERE starboard_locked_message.guild_id = starboard.guild_id AND starboard_locked_message.message_id = $1)"#,
+        message.into_db()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    if posts.is_empty() {
+        return Ok(());
+    }
+
+    let message = channel.message(ctx, message).await?;
+    let source = get_source_channel(ctx, channel).await;
+
+    for post in posts {
+        let color = render::post_color(post.reaction_count, post.min_reactions);
+        let guild_id = post.guild_id.into_serenity();
+        let link = message.id.link(message.channel_id, Some(guild_id));
+
+        let edit = EditMessage::new()
+            .embeds(render::post_embeds(&message, guild_id, color, &source))
+            .components(render::jump_components(link));
+
+        edit_post(
+            ctx,
+            post.starboard_channel.into_serenity(),
+            post.starboard_post_id.into_serenity(),
+            edit,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Edits an existing starboard entry. Returns `false` if its post no longer exists.
+async fn edit_starboard_entry(
+    ctx: &Context,
+    data: &Data,
+    post: MessageId,
+    message: &Message,
+    reactions: usize,
+    starboard: &Starboard,
+) -> Result<bool, Error> {
+    let starboard_channel = starboard.starboard_channel.into_serenity();
 
+    let mut post = match fetch_post(ctx, starboard_channel, post).await {
+        Ok(post) => post,
+        Err(err) if is_not_found(&err) => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+
+    // Entries from before authors and source channels were stored get theirs filled in here
     sqlx::query!(
-        "UPDATE starboard_tracked SET reaction_count = $3 WHERE starboard_tracked.starboard_post_id = $1 AND starboard_tracked.emoji = $2",
-        message.into_db(),
-        emoji_string,
-        reactions as i32
+        "UPDATE starboard_tracked SET reaction_count = $2, author_id = $3, source_channel = $4 WHERE starboard_tracked.starboard_post_id = $1",
+        post.id.into_db(),
+        reactions as i32,
+        message.author.id.into_db(),
+        message.channel_id.into_db()
     ).execute(&data.db)
     .await?;
 
-    let content =
-        post.content.trim_end_matches(char::is_numeric).to_string() + &reactions.to_string();
+    let source = get_source_channel(ctx, message.channel_id).await;
+    let content = render_post_content(data, starboard, reactions as i32, &source).await?;
+    let color = render::post_color(reactions as i32, starboard.min_reactions);
 
-    post.edit(ctx, EditMessage::new().content(content)).await?;
+    let link = message
+        .id
+        .link(message.channel_id, Some(starboard.guild_id.into_serenity()));
 
-    Ok(())
+    // Recounts usually find nothing new, most of all when every entry is recounted on start up
+    if render::is_post_current(&post, &content, color, &link) {
+        return Ok(true);
+    }
+
+    // Only the color changes, the embeds themselves are kept as they are in case the starboard freezes content
+    let embeds = post
+        .embeds
+        .drain(..)
+        .map(|embed| CreateEmbed::from(embed).color(color))
+        .collect();
+
+    let edit = EditMessage::new()
+        .content(content)
+        .embeds(embeds)
+        .components(render::jump_components(link));
+
+    edit_post(ctx, starboard_channel, post.id, edit).await?;
+
+    Ok(true)
+}
+
+/// Renders the content of a post on `starboard`, with the emoji of the tier `reactions` reached
+async fn render_post_content(
+    data: &Data,
+    starboard: &Starboard,
+    reactions: i32,
+    source: &render::SourceChannel,
+) -> Result<String, SQLxError> {
+    let tiers = get_emoji_tiers(data, starboard.id).await?;
+
+    Ok(render::post_content(
+        &starboard.content_template,
+        render::tier_emoji(&starboard.emoji, &tiers, reactions),
+        reactions,
+        Some(source),
+    ))
 }
 
-/// Removes a starboard entry and associated message in provided channel. Fails silently if entry does not exist.
-pub async fn remove_starboard_entry_with_channel(
+/// Removes the entry of a message on `starboard` and its post. Fails silently if entry does not exist.
+pub async fn remove_starboard_entry_from(
     ctx: &Context,
     data: &Data,
     message: MessageId,
-    starboard_channel: ChannelId,
+    starboard: &Starboard,
 ) -> Result<(), Error> {
     let records = sqlx::query!(
-        r#"DELETE FROM starboard_tracked WHERE starboard_tracked.message_id = $1 AND starboard_tracked.starboard_channel = $2
+        r#"DELETE FROM starboard_tracked WHERE starboard_tracked.message_id = $1 AND starboard_tracked.starboard_id = $2
         RETURNING starboard_post_id as "starboard_post_id: database::MessageId""#,
         message.into_db(),
-        starboard_channel.into_db()
+        starboard.id
     )
     .fetch_all(&data.db)
     .await?;
 
+    let starboard_channel = starboard.starboard_channel.into_serenity();
+
     for record in records {
         let message = record.starboard_post_id.into_serenity();
 
-        starboard_channel.delete_message(ctx, message).await?;
+        delete_post(ctx, starboard_channel, message).await?;
     }
 
     Ok(())
@@ -278,48 +1623,467 @@ pub async fn remove_starboard_entry(
     data: &Data,
     message: &MessageId,
 ) -> Result<(), Error> {
-    // Remove + get all entries with the message id. This should return a vec of length zero or one, but is not guaranteed
+    // Remove + get all entries with the message id, one for every starboard it made it onto
     let entries: Vec<_> = sqlx::query!(
-        r#"DELETE FROM starboard_tracked WHERE starboard_tracked.message_id = $1
-        RETURNING starboard_post_id as "starboard_post_id: database::MessageId", starboard_channel as "starboard_channel: database::ChannelId""#,
+        r#"DELETE FROM starboard_tracked USING starboard
+        WHERE starboard.id = starboard_tracked.starboard_id AND starboard_tracked.message_id = $1
+        RETURNING starboard_tracked.starboard_post_id as "starboard_post_id: database::MessageId", starboard.starboard_channel as "starboard_channel: database::ChannelId""#,
         message.into_db(),
     )
     .fetch_all(&data.db)
     .await?;
 
-    // If there are duplicate entries, delete all of them
     for entry in entries {
         let message = entry.starboard_post_id.into_serenity();
 
         let starboard_channel = entry.starboard_channel.into_serenity();
 
-        starboard_channel.delete_message(ctx, message).await?;
+        delete_post(ctx, starboard_channel, message).await?;
     }
 
     Ok(())
 }
 
-/// Remove the starboard tables associated with `channel_id`
-pub async fn delete_starboard_tables(data: &Data, channel_id: ChannelId) -> Result<(), SQLxError> {
-    let id = channel_id.into_db();
+/// A starboard post whose original message was deleted
+struct DeletedSourceEntry {
+    starboard_channel: database::ChannelId,
+    starboard_post_id: database::MessageId,
+}
+
+/// Cleans up after deleted original messages. Their posts stay on starboards that keep history, and are removed from the rest.
+pub async fn handle_deleted_messages(
+    ctx: &Context,
+    data: &Data,
+    messages: &[MessageId],
+) -> Result<(), Error> {
+    let messages: Vec<i64> = messages.iter().map(|message| message.into_db()).collect();
+
+    let kept = sqlx::query_as!(
+        DeletedSourceEntry,
+        r#"UPDATE starboard_tracked SET source_deleted = TRUE FROM starboard
+        WHERE starboard.id = starboard_tracked.starboard_id AND starboard.keep_history AND NOT starboard_tracked.source_deleted
+            AND starboard_tracked.message_id = ANY($1)
+        RETURNING starboard.starboard_channel as "starboard_channel: database::ChannelId", starboard_tracked.starboard_post_id as "starboard_post_id: database::MessageId""#,
+        &messages
+    )
+    .fetch_all(&data.db)
+    .await?;
 
-    let mut trans = data.db.begin().await?;
+    let removed = sqlx::query_as!(
+        DeletedSourceEntry,
+        r#"DELETE FROM starboard_tracked USING starboard
+        WHERE starboard.id = starboard_tracked.starboard_id AND NOT starboard.keep_history AND starboard_tracked.message_id = ANY($1)
+        RETURNING starboard.starboard_channel as "starboard_channel: database::ChannelId", starboard_tracked.starboard_post_id as "starboard_post_id: database::MessageId""#,
+        &messages
+    )
+    .fetch_all(&data.db)
+    .await?;
 
+    // Starboard posts deleted by hand are no longer tracked
     sqlx::query!(
-        "DELETE FROM starboard_tracked WHERE starboard_tracked.starboard_channel = $1",
-        id
+        "DELETE FROM starboard_tracked WHERE starboard_post_id = ANY($1)",
+        &messages
+    )
+    .execute(&data.db)
+    .await?;
+
+    clean_up_deleted_sources(ctx, kept, removed).await
+}
+
+/// Cleans up after a deleted channel the same way as if all of its messages were deleted
+pub async fn handle_deleted_source_channel(
+    ctx: &Context,
+    data: &Data,
+    channel: ChannelId,
+) -> Result<(), Error> {
+    let kept = sqlx::query_as!(
+        DeletedSourceEntry,
+        r#"UPDATE starboard_tracked SET source_deleted = TRUE FROM starboard
+        WHERE starboard.id = starboard_tracked.starboard_id AND starboard.keep_history AND NOT starboard_tracked.source_deleted
+            AND starboard_tracked.source_channel = $1
+        RETURNING starboard.starboard_channel as "starboard_channel: database::ChannelId", starboard_tracked.starboard_post_id as "starboard_post_id: database::MessageId""#,
+        channel.into_db()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let removed = sqlx::query_as!(
+        DeletedSourceEntry,
+        r#"DELETE FROM starboard_tracked USING starboard
+        WHERE starboard.id = starboard_tracked.starboard_id AND NOT starboard.keep_history AND starboard_tracked.source_channel = $1
+        RETURNING starboard.starboard_channel as "starboard_channel: database::ChannelId", starboard_tracked.starboard_post_id as "starboard_post_id: database::MessageId""#,
+        channel.into_db()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    // Posts on forum starboards are threads of their own, deleting one is deleting the post by hand
+    delete_starboard_entry_row(data, MessageId::new(channel.get())).await?;
+
+    clean_up_deleted_sources(ctx, kept, removed).await
+}
+
+/// Takes the jump buttons off kept posts, as there is nothing left to jump to, and deletes removed posts.
+/// Posts that were already deleted by hand are skipped.
+async fn clean_up_deleted_sources(
+    ctx: &Context,
+    kept: Vec<DeletedSourceEntry>,
+    removed: Vec<DeletedSourceEntry>,
+) -> Result<(), Error> {
+    for entry in kept {
+        let edit = EditMessage::new().components(Vec::new());
+
+        let result = edit_post(
+            ctx,
+            entry.starboard_channel.into_serenity(),
+            entry.starboard_post_id.into_serenity(),
+            edit,
+        )
+        .await;
+
+        match result {
+            Ok(_) => (),
+            Err(err) if is_not_found(&err) => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    for entry in removed {
+        let result = delete_post(
+            ctx,
+            entry.starboard_channel.into_serenity(),
+            entry.starboard_post_id.into_serenity(),
+        )
+        .await;
+
+        match result {
+            Ok(()) => (),
+            Err(err) if is_not_found(&err) => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(())
+}
+
+/// A tracked starboard post
+struct TrackedEntry {
+    message_id: database::MessageId,
+    guild_id: database::GuildId,
+    emoji: String,
+    starboard_channel: database::ChannelId,
+    starboard_post_id: database::MessageId,
+    source_channel: Option<database::ChannelId>,
+    source_deleted: bool,
+}
+
+/// Brings every tracked starboard entry back in line with Discord, catching up on anything missed while offline.
+/// Entries whose original message or starboard post is gone are removed, the rest are recounted.
+pub async fn reconcile_starboards(ctx: &Context, data: &Data) -> Result<(), Error> {
+    let mut tiers: HashMap<_, Vec<_>> = HashMap::new();
+
+    let starboards = sqlx::query_as!(
+        Starboard,
+        r#"SELECT id, guild_id as "guild_id!: database::GuildId", emoji as "emoji!", starboard_channel as "starboard_channel: database::ChannelId", min_reactions,
+            self_star, ignore_bots, count_starboard_reactions, freeze_content, keep_promoted, content_template, min_account_days, min_member_days, keep_history
+        FROM starboard ORDER BY starboard.min_reactions"#
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    for starboard in starboards {
+        tiers
+            .entry((starboard.guild_id, starboard.emoji.clone()))
+            .or_default()
+            .push(starboard);
+    }
+
+    let entries = sqlx::query_as!(
+        TrackedEntry,
+        r#"SELECT starboard_tracked.message_id as "message_id: database::MessageId", starboard.guild_id as "guild_id!: database::GuildId", starboard.emoji as "emoji!",
+            starboard.starboard_channel as "starboard_channel: database::ChannelId", starboard_tracked.starboard_post_id as "starboard_post_id: database::MessageId",
+            starboard_tracked.source_channel as "source_channel: database::ChannelId", starboard_tracked.source_deleted
+        FROM starboard_tracked
+        JOIN starboard ON starboard.id = starboard_tracked.starboard_id"#
     )
-    .execute(trans.as_mut())
+    .fetch_all(&data.db)
     .await?;
 
+    tracing::info!("Reconciling {} starboard entries", entries.len());
+
+    let mut recounted = HashSet::new();
+
+    for entry in entries {
+        let message_id = entry.message_id.into_serenity();
+        let tiers = tiers
+            .get(&(entry.guild_id, entry.emoji.clone()))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        if let Err(err) = reconcile_starboard_entry(ctx, data, entry, tiers, &mut recounted).await {
+            tracing::warn!("Could not reconcile starboard entry for message {message_id}: {err}");
+        }
+
+        tokio::time::sleep(RECONCILE_DELAY).await;
+    }
+
+    tracing::info!("Finished reconciling starboard entries");
+
+    Ok(())
+}
+
+/// Checks that the post of `entry` still exists and recounts the message on every tier of its emoji,
+/// unless that already happened through another of its entries
+async fn reconcile_starboard_entry(
+    ctx: &Context,
+    data: &Data,
+    entry: TrackedEntry,
+    tiers: &[Starboard],
+    recounted: &mut HashSet<(MessageId, String)>,
+) -> Result<(), Error> {
+    let message_id = entry.message_id.into_serenity();
+    let starboard_channel = entry.starboard_channel.into_serenity();
+    let starboard_post = entry.starboard_post_id.into_serenity();
+
+    let post = match fetch_post(ctx, starboard_channel, starboard_post).await {
+        Ok(post) => post,
+        Err(err) if is_not_found(&err) => {
+            delete_starboard_entry_row(data, starboard_post).await?;
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    // Kept history has nothing left to recount
+    if entry.source_deleted {
+        return Ok(());
+    }
+
+    // Entries from before the source channel was stored can recover it from the link in the post
+    let source_channel = match entry.source_channel {
+        Some(channel) => channel.into_serenity(),
+        None => {
+            let Some((_, channel, _)) = post.content.split(' ').find_map(parse_message_url) else {
+                return Ok(());
+            };
+
+            sqlx::query!(
+                "UPDATE starboard_tracked SET source_channel = $1 WHERE starboard_tracked.starboard_post_id = $2",
+                channel.into_db(),
+                starboard_post.into_db()
+            )
+            .execute(&data.db)
+            .await?;
+
+            channel
+        }
+    };
+
+    if !recounted.insert((message_id, entry.emoji.clone())) {
+        return Ok(());
+    }
+
+    let message = match source_channel.message(ctx, message_id).await {
+        Ok(message) => message,
+        Err(err) if is_not_found(&err) => {
+            handle_deleted_messages(ctx, data, &[message_id]).await?;
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let emoji = ReactionType::try_from(entry.emoji.as_str())?;
+
+    update_starboard_entry(ctx, data, message, emoji, tiers).await?;
+
+    Ok(())
+}
+
+/// Removes the tracking of a starboard post without touching Discord
+async fn delete_starboard_entry_row(
+    data: &Data,
+    starboard_post: MessageId,
+) -> Result<(), SQLxError> {
     sqlx::query!(
-        "DELETE FROM starboard WHERE starboard.starboard_channel = $1",
+        "DELETE FROM starboard_tracked WHERE starboard_tracked.starboard_post_id = $1",
+        starboard_post.into_db()
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(())
+}
+
+/// Where a message was sent, looking through threads to their parent channel
+async fn get_source_channel(ctx: &Context, channel_id: ChannelId) -> render::SourceChannel {
+    let Ok(Channel::Guild(channel)) = channel_id.to_channel(ctx).await else {
+        return render::SourceChannel {
+            channel: channel_id,
+            name: None,
+            thread: None,
+        };
+    };
+
+    match channel
+        .parent_id
+        .filter(|_| channel.thread_metadata.is_some())
+    {
+        Some(parent) => render::SourceChannel {
+            channel: parent,
+            name: parent.name(ctx).await.ok(),
+            thread: Some(channel.name),
+        },
+        None => render::SourceChannel {
+            channel: channel.id,
+            name: Some(channel.name),
+            thread: None,
+        },
+    }
+}
+
+/// The forum channel a starboard posts in, if it is one
+async fn get_starboard_forum(ctx: &Context, channel: ChannelId) -> Option<GuildChannel> {
+    match channel.to_channel(ctx).await {
+        Ok(Channel::Guild(channel)) if channel.kind == ChannelType::Forum => Some(channel),
+        _ => None,
+    }
+}
+
+/// The channel a starboard post was sent in.
+/// Posts on forum channels are the first message of their own thread, which shares their id.
+async fn post_channel(ctx: &Context, starboard_channel: ChannelId, post: MessageId) -> ChannelId {
+    match get_starboard_forum(ctx, starboard_channel).await {
+        Some(_) => ChannelId::new(post.get()),
+        None => starboard_channel,
+    }
+}
+
+/// A link to a starboard post, which for forum channels is inside the post's own thread
+pub async fn post_link(
+    ctx: &Context,
+    guild_id: GuildId,
+    starboard_channel: ChannelId,
+    post: MessageId,
+) -> String {
+    let channel = post_channel(ctx, starboard_channel, post).await;
+
+    post.link(channel, Some(guild_id))
+}
+
+/// Sends a post to a starboard, as a new forum post titled `title` if the starboard is a forum channel
+async fn send_post(
+    ctx: &Context,
+    starboard_channel: ChannelId,
+    post: CreateMessage,
+    title: String,
+    emoji: &str,
+    source: Option<&render::SourceChannel>,
+) -> Result<MessageId, SerenityError> {
+    let Some(forum) = get_starboard_forum(ctx, starboard_channel).await else {
+        return Ok(starboard_channel.send_message(ctx, post).await?.id);
+    };
+
+    let tags = render::forum_tags(&forum.available_tags, emoji, source);
+    let thread = starboard_channel
+        .create_forum_post(
+            ctx,
+            CreateForumPost::new(title, post).set_applied_tags(tags),
+        )
+        .await?;
+
+    Ok(MessageId::new(thread.id.get()))
+}
+
+async fn fetch_post(
+    ctx: &Context,
+    starboard_channel: ChannelId,
+    post: MessageId,
+) -> Result<Message, SerenityError> {
+    post_channel(ctx, starboard_channel, post)
+        .await
+        .message(ctx, post)
+        .await
+}
+
+/// Edits a starboard post. Archived forum posts are reopened for the edit and archived again after.
+async fn edit_post(
+    ctx: &Context,
+    starboard_channel: ChannelId,
+    post: MessageId,
+    edit: EditMessage,
+) -> Result<Message, SerenityError> {
+    let channel = post_channel(ctx, starboard_channel, post).await;
+
+    if channel == starboard_channel {
+        return channel.edit_message(ctx, post, edit).await;
+    }
+
+    let archived = match channel.to_channel(ctx).await? {
+        Channel::Guild(thread) => thread
+            .thread_metadata
+            .is_some_and(|metadata| metadata.archived),
+        _ => false,
+    };
+
+    if archived {
+        channel
+            .edit_thread(ctx, EditThread::new().archived(false))
+            .await?;
+    }
+
+    let result = channel.edit_message(ctx, post, edit).await;
+
+    if archived {
+        channel
+            .edit_thread(ctx, EditThread::new().archived(true))
+            .await?;
+    }
+
+    result
+}
+
+/// Deletes a starboard post, along with its thread if it is a forum post
+async fn delete_post(
+    ctx: &Context,
+    starboard_channel: ChannelId,
+    post: MessageId,
+) -> Result<(), SerenityError> {
+    let channel = post_channel(ctx, starboard_channel, post).await;
+
+    if channel == starboard_channel {
+        channel.delete_message(ctx, post).await
+    } else {
+        channel.delete(ctx).await.map(|_| ())
+    }
+}
+
+/// Whether a request failed because the thing it asked for does not exist (anymore)
+fn is_not_found(err: &SerenityError) -> bool {
+    matches!(err, SerenityError::Http(err) if err.status_code() == Some(StatusCode::NOT_FOUND))
+}
+
+/// Remove the starboard `id` and its tracked entries. Returns `false` if the guild has no such starboard.
+pub async fn delete_starboard(data: &Data, guild_id: GuildId, id: i32) -> Result<bool, SQLxError> {
+    let result = sqlx::query!(
+        "DELETE FROM starboard WHERE starboard.guild_id = $1 AND starboard.id = $2",
+        guild_id.into_db(),
         id
     )
-    .execute(trans.as_mut())
+    .execute(&data.db)
     .await?;
 
-    trans.commit().await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove the starboard tables associated with `channel_id`
+pub async fn delete_starboard_tables(data: &Data, channel_id: ChannelId) -> Result<(), SQLxError> {
+    // Filters and tracked entries go along with their starboard
+    sqlx::query!(
+        "DELETE FROM starboard WHERE starboard.starboard_channel = $1",
+        channel_id.into_db()
+    )
+    .execute(&data.db)
+    .await?;
 
     Ok(())
 }
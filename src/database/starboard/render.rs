@@ -0,0 +1,414 @@
+use core::fmt::Display;
+use core::mem;
+use std::fmt::Write;
+use std::iter;
+
+use super::EmojiTier;
+use crate::util::{
+    truncate, EMBED_AUTHOR_LENGTH, EMBED_DESCRIPTION_LENGTH, EMBED_FIELD_LENGTH,
+    MAX_CONTENT_LENGTH, TOTAL_EMBED_LENGTH,
+};
+use crate::EMBED_COLOR;
+use poise::serenity_prelude::{
+    ActionRowComponent, Attachment, ButtonKind, ChannelId, Color, CreateActionRow, CreateButton,
+    CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, Embed, ForumEmoji, ForumTag, ForumTagId,
+    GuildId, Mentionable, Message, StickerFormatType,
+};
+
+/// Discord allows at most this many embeds on a single message
+const MAX_EMBEDS: usize = 10;
+
+/// Discord shows at most this many images from embeds sharing a URL together
+const GALLERY_SIZE: usize = 4;
+
+/// The embed color of posts that have many more reactions than the starboard needs
+const HOT_COLOR: Color = Color::from_rgb(255, 85, 0);
+
+/// How many times the minimum reactions a post needs to reach [`HOT_COLOR`]
+const HOT_MULTIPLIER: f64 = 5.0;
+
+/// Discord allows at most this many characters in the title of a forum post
+const FORUM_TITLE_LENGTH: usize = 100;
+
+/// Discord allows at most this many tags on a forum post
+const MAX_FORUM_TAGS: usize = 5;
+
+/// How much of a replied message is quoted
+const REPLY_CONTENT_LENGTH: usize = 300;
+
+/// Room kept at the end of a file list for saying how many files did not fit
+const OMITTED_FILES_LENGTH: usize = 16;
+
+/// Where an original message was sent. Messages in threads and forum posts are shown under the parent channel.
+pub struct SourceChannel {
+    /// The channel, or the parent of the thread
+    pub channel: ChannelId,
+    /// The name of the channel, or of the parent of the thread
+    pub name: Option<String>,
+    /// The name of the thread the message is in
+    pub thread: Option<String>,
+}
+
+impl Display for SourceChannel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.thread {
+            Some(thread) => write!(f, "{} › {thread}", self.channel.mention()),
+            None => write!(f, "{}", self.channel.mention()),
+        }
+    }
+}
+
+/// The text content of a starboard post, filling `{emoji}`, `{count}` and `{channel}` into the starboard's template.
+/// Anything else in braces is left as is, and a template that fills in too much is cut to fit in a message.
+pub fn post_content(
+    template: &str,
+    emoji: &str,
+    reactions: impl Display,
+    channel: Option<&SourceChannel>,
+) -> String {
+    let mut content = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        content.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+
+        match &rest[1..end] {
+            "emoji" => content.push_str(emoji),
+            "count" => write!(&mut content, "{reactions}").unwrap(),
+            "channel" => {
+                if let Some(channel) = channel {
+                    write!(&mut content, "{channel}").unwrap();
+                }
+            }
+            _ => content.push_str(&rest[..=end]),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    content.push_str(rest);
+    truncate(&content, MAX_CONTENT_LENGTH)
+}
+
+/// The emoji to show for `reactions`, the one of the highest tier reached or `base` if none are
+pub fn tier_emoji<'a>(base: &'a str, tiers: &'a [EmojiTier], reactions: i32) -> &'a str {
+    tiers
+        .iter()
+        .filter(|tier| reactions >= tier.min_count)
+        .max_by_key(|tier| tier.min_count)
+        .map_or(base, |tier| &tier.emoji)
+}
+
+/// The embed color of a post, going from [`EMBED_COLOR`] at the minimum reactions to [`HOT_COLOR`] as it gets more
+pub fn post_color(reactions: i32, min_reactions: i32) -> Color {
+    let min_reactions = f64::from(min_reactions.max(1));
+    let progress =
+        (f64::from(reactions) - min_reactions) / (min_reactions * (HOT_MULTIPLIER - 1.0));
+    let progress = progress.clamp(0.0, 1.0);
+
+    let mix = |from: u8, to: u8| {
+        (f64::from(from) + (f64::from(to) - f64::from(from)) * progress).round() as u8
+    };
+
+    Color::from_rgb(
+        mix(EMBED_COLOR.r(), HOT_COLOR.r()),
+        mix(EMBED_COLOR.g(), HOT_COLOR.g()),
+        mix(EMBED_COLOR.b(), HOT_COLOR.b()),
+    )
+}
+
+/// A link button leading back to the original message
+pub fn jump_components(link: String) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new_link(link).label("Jump to message")
+    ])]
+}
+
+/// Whether a post already shows `content`, has embeds of `color` and jumps to `link`, so editing it would change nothing
+pub fn is_post_current(post: &Message, content: &str, color: Color, link: &str) -> bool {
+    let jumps_to_link = post
+        .components
+        .iter()
+        .flat_map(|row| &row.components)
+        .any(|component| {
+            matches!(component, ActionRowComponent::Button(button)
+                if matches!(&button.data, ButtonKind::Link { url } if url == link))
+        });
+
+    post.content == content
+        && post.embeds.iter().all(|embed| embed.colour == Some(color))
+        && jumps_to_link
+}
+
+/// The title of a forum post for a message, its author followed by as much of its content as fits
+pub fn forum_post_title(author: &str, content: &str) -> String {
+    let title = match content.lines().next().filter(|line| !line.is_empty()) {
+        Some(line) => format!("{author}: {line}"),
+        None => format!("Message from {author}"),
+    };
+
+    truncate(&title, FORUM_TITLE_LENGTH)
+}
+
+/// The title of a forum post for a copy of an existing starboard post, taken from the original message it shows
+pub fn copied_post_title(post: &Message) -> String {
+    match post.embeds.first() {
+        Some(embed) => forum_post_title(
+            embed
+                .author
+                .as_ref()
+                .map_or("someone", |author| &author.name),
+            embed.description.as_deref().unwrap_or_default(),
+        ),
+        None => forum_post_title("someone", &post.content),
+    }
+}
+
+/// The tags of a forum to put on a post, those showing the starboard's emoji or named after the original channel
+pub fn forum_tags(
+    available: &[ForumTag],
+    emoji: &str,
+    source: Option<&SourceChannel>,
+) -> Vec<ForumTagId> {
+    let channel_name = source.and_then(|source| source.name.as_deref());
+
+    available
+        .iter()
+        .filter(|tag| {
+            let emoji_matches = match &tag.emoji {
+                Some(ForumEmoji::Name(name)) => name == emoji,
+                Some(ForumEmoji::Id(id)) => emoji.contains(&id.to_string()),
+                _ => false,
+            };
+
+            emoji_matches || channel_name.is_some_and(|name| tag.name.eq_ignore_ascii_case(name))
+        })
+        .map(|tag| tag.id)
+        .take(MAX_FORUM_TAGS)
+        .collect()
+}
+
+/// The embeds of a starboard post, showing the message it replies to and the original message along with its images,
+/// stickers, files and link previews. Text is cut down to fit every embed limit.
+pub fn post_embeds(
+    message: &Message,
+    guild_id: GuildId,
+    color: Color,
+    source: &SourceChannel,
+) -> Vec<CreateEmbed> {
+    let link = message.id.link(message.channel_id, Some(guild_id));
+
+    let (images, files): (Vec<&Attachment>, Vec<&Attachment>) =
+        message.attachments.iter().partition(|a| is_image(a));
+
+    let mut images: Vec<String> = images.into_iter().map(|a| a.url.clone()).collect();
+
+    let mut unrenderable_stickers = Vec::new();
+
+    for sticker in &message.sticker_items {
+        match (sticker.format_type, sticker.image_url()) {
+            (StickerFormatType::Lottie, _) | (_, None) => unrenderable_stickers.push(&sticker.name),
+            (_, Some(url)) => images.push(url),
+        }
+    }
+
+    let preview = message.embeds.first();
+
+    // Links to images and gifs are shown as if they were uploaded
+    if let Some(url) = preview.and_then(embedded_image) {
+        images.push(url);
+    }
+
+    let mut fields = Vec::new();
+
+    if source.thread.is_some() {
+        fields.push(("Thread:", truncate(&source.to_string(), EMBED_FIELD_LENGTH)));
+    }
+
+    let mut thumbnail = None;
+
+    if let Some(preview) = preview.filter(|p| embedded_image(p).is_none()) {
+        if let Some(title) = &preview.title {
+            let linked = preview
+                .url
+                .as_ref()
+                .map(|url| format!("[{title}]({url})"))
+                .filter(|linked| linked.chars().count() <= EMBED_FIELD_LENGTH);
+
+            fields.push((
+                "Embed:",
+                linked.unwrap_or_else(|| truncate(title, EMBED_FIELD_LENGTH)),
+            ));
+        }
+
+        thumbnail = preview
+            .thumbnail
+            .as_ref()
+            .map(|t| &t.url)
+            .or(preview.image.as_ref().map(|i| &i.url));
+    }
+
+    if !files.is_empty() {
+        fields.push(("Files:", file_list(&files)));
+    }
+
+    if !unrenderable_stickers.is_empty() {
+        let stickers = unrenderable_stickers
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        fields.push(("Stickers:", truncate(&stickers, EMBED_FIELD_LENGTH)));
+    }
+
+    let footer = message.id.to_string();
+    let author = truncate(&message.author.name, EMBED_AUTHOR_LENGTH);
+
+    let reply = message
+        .referenced_message
+        .as_deref()
+        .map(|reply| reply_embed(reply, guild_id, color));
+
+    // The description gets whatever is left once everything else is accounted for
+    let used = author.chars().count()
+        + footer.chars().count()
+        + reply.as_ref().map_or(0, |(_, length)| *length)
+        + fields
+            .iter()
+            .map(|(name, value)| name.chars().count() + value.chars().count())
+            .sum::<usize>();
+    let description_length = EMBED_DESCRIPTION_LENGTH.min(TOTAL_EMBED_LENGTH.saturating_sub(used));
+
+    let mut main_embed = CreateEmbed::new()
+        .author(CreateEmbedAuthor::new(author).icon_url(message.author.face()))
+        .url(gallery_url(&link, 0))
+        .description(truncate(&message.content, description_length))
+        .color(color);
+
+    for (name, value) in fields {
+        main_embed = main_embed.field(name, value, false);
+    }
+
+    if let Some(thumbnail) = thumbnail {
+        main_embed = main_embed.thumbnail(thumbnail);
+    }
+
+    let mut extra_embeds = Vec::new();
+    let max_images = MAX_EMBEDS - usize::from(reply.is_some());
+
+    if let Some((first, extra)) = images.split_first() {
+        main_embed = main_embed.image(first);
+
+        for (i, image) in (1..).zip(extra.iter().take(max_images - 1)) {
+            let embed = CreateEmbed::new()
+                .color(color)
+                .url(gallery_url(&link, i / GALLERY_SIZE))
+                .image(image);
+
+            extra_embeds.push(embed);
+        }
+    }
+
+    let last = extra_embeds.last_mut().unwrap_or(&mut main_embed);
+    *last = mem::take(last)
+        .footer(CreateEmbedFooter::new(footer))
+        .timestamp(message.timestamp);
+
+    reply
+        .map(|(embed, _)| embed)
+        .into_iter()
+        .chain(iter::once(main_embed))
+        .chain(extra_embeds)
+        .collect()
+}
+
+/// A quote of the message being replied to, with its author, a short part of its content, its first image and a jump link.
+/// Returns the embed along with how many characters of it count towards [`TOTAL_EMBED_LENGTH`].
+fn reply_embed(reply: &Message, guild_id: GuildId, color: Color) -> (CreateEmbed, usize) {
+    // Replied messages come without a guild, which their own link needs
+    let link = reply.id.link(reply.channel_id, Some(guild_id));
+
+    let author = truncate(
+        &format!("Replying to {}", reply.author.name),
+        EMBED_AUTHOR_LENGTH,
+    );
+    let description = format!(
+        "{}\n[Jump to reply]({link})",
+        truncate(&reply.content, REPLY_CONTENT_LENGTH)
+    );
+    let length = author.chars().count() + description.chars().count();
+
+    let mut embed = CreateEmbed::new()
+        .author(
+            CreateEmbedAuthor::new(author)
+                .icon_url(reply.author.face())
+                .url(link),
+        )
+        .description(description)
+        .color(color);
+
+    let image = reply
+        .attachments
+        .iter()
+        .find(|a| is_image(a))
+        .map(|a| a.url.clone())
+        .or_else(|| reply.embeds.first().and_then(embedded_image));
+
+    if let Some(image) = image {
+        embed = embed.thumbnail(image);
+    }
+
+    (embed, length)
+}
+
+/// Links to each file, leaving out those that do not fit in an embed field
+fn file_list(files: &[&Attachment]) -> String {
+    let mut list = String::new();
+
+    for (i, file) in files.iter().enumerate() {
+        let line = format!("[{}]({})\n", file.filename, file.url);
+
+        // Always leave room to say how many files are missing
+        if list.chars().count() + line.chars().count() + OMITTED_FILES_LENGTH > EMBED_FIELD_LENGTH {
+            write!(&mut list, "and {} more", files.len() - i).unwrap();
+            break;
+        }
+
+        list.push_str(&line);
+    }
+
+    list
+}
+
+fn is_image(attachment: &Attachment) -> bool {
+    attachment
+        .content_type
+        .as_deref()
+        .is_some_and(|t| t.starts_with("image/"))
+}
+
+/// Embeds with the same URL are shown together as a gallery of up to [`GALLERY_SIZE`] images
+fn gallery_url(link: &str, gallery: usize) -> String {
+    match gallery {
+        0 => link.to_string(),
+        _ => format!("{link}?gallery={gallery}"),
+    }
+}
+
+/// The image behind a link preview that is only an image or gif
+fn embedded_image(embed: &Embed) -> Option<String> {
+    match embed.kind.as_deref() {
+        Some("image" | "gifv") => embed
+            .thumbnail
+            .as_ref()
+            .map(|t| t.url.clone())
+            .or(embed.url.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,744 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    add_starboard_channel_filter, add_starboard_emoji_tier, add_starboard_role_filter,
+    add_starboard_tables, fetch_post, get_emoji_tiers, get_starboard_filters, get_starboard_tiers,
+    get_starboards, update_starboard_entry, EmojiTier, Starboard, DEFAULT_CONTENT_TEMPLATE,
+    MAX_TEMPLATE_LENGTH,
+};
+use crate::database::{self, IntoDatabase};
+use crate::{Data, Error};
+use poise::serenity_prelude::{
+    ChannelId, Context, GuildId, MessageId, ReactionType, RoleId, UserId,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::Error as SQLxError;
+
+/// The version of the format written by [`export_starboards`]
+const EXPORT_VERSION: u32 = 1;
+
+/// The emoji of starboards imported from bots that do not say which one they used
+const DEFAULT_IMPORT_EMOJI: &str = "⭐";
+
+/// The starboards of a guild and everything posted on them
+#[derive(Serialize, Deserialize)]
+pub struct StarboardExport {
+    pub version: u32,
+    pub starboards: Vec<ExportedStarboard>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportedStarboard {
+    pub emoji: String,
+    /// Missing from the exports of some other bots, the channel picked on import is used instead
+    pub starboard_channel: Option<ChannelId>,
+    pub min_reactions: i32,
+    pub self_star: bool,
+    pub ignore_bots: bool,
+    pub count_starboard_reactions: bool,
+    pub freeze_content: bool,
+    pub keep_promoted: bool,
+    pub keep_history: bool,
+    pub content_template: String,
+    pub min_account_days: i32,
+    pub min_member_days: i32,
+    pub emoji_tiers: Vec<EmojiTier>,
+    pub allowed_channels: Vec<ChannelId>,
+    pub denied_channels: Vec<ChannelId>,
+    pub required_roles: Vec<RoleId>,
+    pub entries: Vec<ExportedEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportedEntry {
+    pub message_id: MessageId,
+    pub source_channel: Option<ChannelId>,
+    pub starboard_post_id: Option<MessageId>,
+    pub author_id: Option<UserId>,
+    pub reaction_count: i32,
+    pub source_deleted: bool,
+}
+
+/// Exports other starboard bots commonly produce, either a single board or just its posts.
+/// Serenity reads IDs both as numbers and as strings, which covers bots written in JavaScript.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ForeignExport {
+    Board(ForeignBoard),
+    Entries(Vec<ForeignEntry>),
+}
+
+#[derive(Deserialize)]
+struct ForeignBoard {
+    #[serde(default, alias = "star_emoji", alias = "starEmoji")]
+    emoji: Option<String>,
+    #[serde(
+        default,
+        alias = "threshold",
+        alias = "required_stars",
+        alias = "requiredStars",
+        alias = "min_stars",
+        alias = "minStars"
+    )]
+    min_reactions: Option<i32>,
+    #[serde(
+        default,
+        alias = "channel_id",
+        alias = "channelId",
+        alias = "starboard_channel_id",
+        alias = "starboardChannelId"
+    )]
+    starboard_channel: Option<ChannelId>,
+    #[serde(alias = "messages", alias = "starred", alias = "posts")]
+    entries: Vec<ForeignEntry>,
+}
+
+#[derive(Deserialize)]
+struct ForeignEntry {
+    #[serde(
+        alias = "messageId",
+        alias = "original_message_id",
+        alias = "originalMessageId",
+        alias = "id"
+    )]
+    message_id: MessageId,
+    #[serde(
+        default,
+        alias = "channel_id",
+        alias = "channelId",
+        alias = "original_channel_id",
+        alias = "originalChannelId"
+    )]
+    source_channel: Option<ChannelId>,
+    #[serde(
+        default,
+        alias = "starboard_message_id",
+        alias = "starboardMessageId",
+        alias = "star_message_id",
+        alias = "starMessageId"
+    )]
+    starboard_post_id: Option<MessageId>,
+    #[serde(default, alias = "authorId", alias = "user_id", alias = "userId")]
+    author_id: Option<UserId>,
+    #[serde(
+        default,
+        alias = "stars",
+        alias = "starCount",
+        alias = "star_count",
+        alias = "points",
+        alias = "count"
+    )]
+    reaction_count: i32,
+}
+
+impl From<ForeignEntry> for ExportedEntry {
+    fn from(entry: ForeignEntry) -> Self {
+        ExportedEntry {
+            message_id: entry.message_id,
+            source_channel: entry.source_channel,
+            starboard_post_id: entry.starboard_post_id,
+            author_id: entry.author_id,
+            reaction_count: entry.reaction_count,
+            source_deleted: false,
+        }
+    }
+}
+
+impl From<ForeignBoard> for ExportedStarboard {
+    fn from(board: ForeignBoard) -> Self {
+        // Without a threshold, the lowest one that keeps every imported post on the board
+        let min_reactions = board.min_reactions.unwrap_or_else(|| {
+            board
+                .entries
+                .iter()
+                .map(|entry| entry.reaction_count)
+                .min()
+                .unwrap_or(1)
+        });
+
+        ExportedStarboard {
+            emoji: board
+                .emoji
+                .unwrap_or_else(|| DEFAULT_IMPORT_EMOJI.to_string()),
+            starboard_channel: board.starboard_channel,
+            min_reactions: min_reactions.max(1),
+            self_star: false,
+            ignore_bots: false,
+            count_starboard_reactions: false,
+            freeze_content: false,
+            keep_promoted: true,
+            keep_history: false,
+            content_template: DEFAULT_CONTENT_TEMPLATE.to_string(),
+            min_account_days: 0,
+            min_member_days: 0,
+            emoji_tiers: Vec::new(),
+            allowed_channels: Vec::new(),
+            denied_channels: Vec::new(),
+            required_roles: Vec::new(),
+            entries: board.entries.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Reads an export of this bot, or of another starboard bot. Returns `None` if it is neither.
+pub fn parse_starboard_import(file: &[u8]) -> Option<StarboardExport> {
+    if let Ok(export) = serde_json::from_slice::<StarboardExport>(file) {
+        return (export.version <= EXPORT_VERSION).then_some(export);
+    }
+
+    let board = match serde_json::from_slice(file).ok()? {
+        ForeignExport::Board(board) => board,
+        ForeignExport::Entries(entries) => ForeignBoard {
+            emoji: None,
+            min_reactions: None,
+            starboard_channel: None,
+            entries,
+        },
+    };
+
+    Some(StarboardExport {
+        version: EXPORT_VERSION,
+        starboards: vec![board.into()],
+    })
+}
+
+/// Checks an import against the same limits as the commands that create and edit starboards,
+/// and that it does not name channels of another guild, describing the first problem found
+pub async fn validate_starboard_import(
+    ctx: &Context,
+    guild_id: GuildId,
+    import: &StarboardExport,
+) -> Result<(), String> {
+    check_import_limits(import)?;
+
+    // Channels that can not be seen are left for the import to skip
+    for channel in import_channels(import) {
+        if channel_guild(ctx, channel)
+            .await
+            .is_some_and(|guild| guild != guild_id)
+        {
+            return Err(format!("channel {channel} is not in this server"));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_import_limits(import: &StarboardExport) -> Result<(), String> {
+    for starboard in &import.starboards {
+        if ReactionType::try_from(starboard.emoji.as_str()).is_err() {
+            return Err(format!("{} is not an emoji", starboard.emoji));
+        }
+
+        if starboard.min_reactions <= 0 {
+            return Err(format!(
+                "the {} starboard needs at least one reaction",
+                starboard.emoji
+            ));
+        }
+
+        if starboard.content_template.chars().count() > MAX_TEMPLATE_LENGTH {
+            return Err(format!(
+                "the template of the {} starboard is longer than {MAX_TEMPLATE_LENGTH} characters",
+                starboard.emoji
+            ));
+        }
+
+        let days = 0..=i32::from(u16::MAX);
+
+        if !days.contains(&starboard.min_account_days) || !days.contains(&starboard.min_member_days)
+        {
+            return Err(format!(
+                "the age limits of the {} starboard are out of range",
+                starboard.emoji
+            ));
+        }
+
+        for tier in &starboard.emoji_tiers {
+            if tier.min_count <= 0 || ReactionType::try_from(tier.emoji.as_str()).is_err() {
+                return Err(format!(
+                    "the {} starboard has an invalid emoji tier",
+                    starboard.emoji
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Every channel an import names, whether as a starboard, in a filter or as where a post came from
+fn import_channels(import: &StarboardExport) -> HashSet<ChannelId> {
+    import
+        .starboards
+        .iter()
+        .flat_map(|starboard| {
+            starboard
+                .starboard_channel
+                .iter()
+                .chain(&starboard.allowed_channels)
+                .chain(&starboard.denied_channels)
+                .chain(
+                    starboard
+                        .entries
+                        .iter()
+                        .filter_map(|entry| entry.source_channel.as_ref()),
+                )
+        })
+        .copied()
+        .collect()
+}
+
+/// The guild a channel is in, or `None` if the bot can not see it
+async fn channel_guild(ctx: &Context, channel: ChannelId) -> Option<GuildId> {
+    channel
+        .to_channel(ctx)
+        .await
+        .ok()?
+        .guild()
+        .map(|channel| channel.guild_id)
+}
+
+/// The configuration and tracked posts of every starboard of a guild
+pub async fn export_starboards(
+    data: &Data,
+    guild_id: GuildId,
+) -> Result<StarboardExport, SQLxError> {
+    let mut starboards = Vec::new();
+
+    for starboard in get_starboards(data, guild_id).await? {
+        let filters = get_starboard_filters(data, starboard.id).await?;
+        let emoji_tiers = get_emoji_tiers(data, starboard.id).await?;
+
+        let entries = sqlx::query!(
+            r#"SELECT message_id as "message_id: database::MessageId", starboard_post_id as "starboard_post_id: database::MessageId",
+                source_channel as "source_channel: database::ChannelId", author_id as "author_id: database::UserId", reaction_count, source_deleted
+            FROM starboard_tracked WHERE starboard_tracked.starboard_id = $1"#,
+            starboard.id
+        )
+        .fetch_all(&data.db)
+        .await?
+        .into_iter()
+        .map(|record| ExportedEntry {
+            message_id: record.message_id.into_serenity(),
+            source_channel: record.source_channel.map(database::ChannelId::into_serenity),
+            starboard_post_id: Some(record.starboard_post_id.into_serenity()),
+            author_id: record.author_id.map(database::UserId::into_serenity),
+            reaction_count: record.reaction_count,
+            source_deleted: record.source_deleted,
+        })
+        .collect();
+
+        starboards.push(ExportedStarboard {
+            emoji: starboard.emoji,
+            starboard_channel: Some(starboard.starboard_channel.into_serenity()),
+            min_reactions: starboard.min_reactions,
+            self_star: starboard.self_star,
+            ignore_bots: starboard.ignore_bots,
+            count_starboard_reactions: starboard.count_starboard_reactions,
+            freeze_content: starboard.freeze_content,
+            keep_promoted: starboard.keep_promoted,
+            keep_history: starboard.keep_history,
+            content_template: starboard.content_template,
+            min_account_days: starboard.min_account_days,
+            min_member_days: starboard.min_member_days,
+            emoji_tiers,
+            allowed_channels: filters.allowed_channels,
+            denied_channels: filters.denied_channels,
+            required_roles: filters.required_roles,
+            entries,
+        });
+    }
+
+    Ok(StarboardExport {
+        version: EXPORT_VERSION,
+        starboards,
+    })
+}
+
+/// What happened to the starboards and posts of an import
+#[derive(Default)]
+pub struct ImportSummary {
+    pub starboards: usize,
+    /// Starboards left out as their channel is not in the guild and no other channel was given
+    pub skipped_starboards: usize,
+    /// Posts that were still there and only needed to be tracked again
+    pub relinked: usize,
+    pub reposted: usize,
+    /// Posts left out as their message is locked, removed, from a blacklisted user or no longer has enough reactions
+    pub declined: usize,
+    /// Posts whose original message and old post could both not be found in the guild
+    pub lost: usize,
+}
+
+/// Recreates the starboards of an export in a guild and brings back their posts.
+/// Starboards whose channel is not in the guild are put in `fallback_channel`, and a starboard with the same emoji
+/// and threshold as an existing one is merged into it.
+///
+/// Posts still in the starboard channel are tracked again, otherwise they are posted again from the original message.
+/// Only messages in channels of the guild are read, so an import can not bring in posts from other servers.
+pub async fn import_starboards(
+    ctx: &Context,
+    data: &Data,
+    guild_id: GuildId,
+    import: StarboardExport,
+    fallback_channel: Option<ChannelId>,
+) -> Result<ImportSummary, Error> {
+    let channels = guild_id.channels(ctx).await?;
+    let roles = guild_id.roles(ctx).await?;
+
+    let mut sources = HashSet::new();
+
+    for channel in import
+        .starboards
+        .iter()
+        .flat_map(|starboard| &starboard.entries)
+        .filter_map(|entry| entry.source_channel)
+    {
+        if !sources.contains(&channel) && channel_guild(ctx, channel).await == Some(guild_id) {
+            sources.insert(channel);
+        }
+    }
+
+    let mut summary = ImportSummary::default();
+
+    for exported in import.starboards {
+        let channel = exported
+            .starboard_channel
+            .or(fallback_channel)
+            .filter(|channel| channels.contains_key(channel));
+
+        let Some(channel) = channel else {
+            summary.skipped_starboards += 1;
+            continue;
+        };
+
+        let existing = sqlx::query!(
+            "SELECT id FROM starboard WHERE starboard.guild_id = $1 AND starboard.emoji = $2 AND starboard.min_reactions = $3",
+            guild_id.into_db(),
+            exported.emoji,
+            exported.min_reactions
+        )
+        .fetch_optional(&data.db)
+        .await?;
+
+        let id = match existing {
+            Some(record) => record.id,
+            None => {
+                let starboard = Starboard {
+                    // Picked by the database
+                    id: 0,
+                    guild_id: guild_id.into(),
+                    emoji: exported.emoji.clone(),
+                    starboard_channel: channel.into(),
+                    min_reactions: exported.min_reactions,
+                    self_star: exported.self_star,
+                    ignore_bots: exported.ignore_bots,
+                    count_starboard_reactions: exported.count_starboard_reactions,
+                    freeze_content: exported.freeze_content,
+                    keep_promoted: exported.keep_promoted,
+                    content_template: exported.content_template.clone(),
+                    min_account_days: exported.min_account_days,
+                    min_member_days: exported.min_member_days,
+                    keep_history: exported.keep_history,
+                };
+
+                let id = add_starboard_tables(data, &starboard).await?;
+                import_starboard_settings(data, guild_id, id, &exported, &channels, &roles).await?;
+                id
+            }
+        };
+
+        let Some(starboard) = get_starboards(data, guild_id)
+            .await?
+            .into_iter()
+            .find(|starboard| starboard.id == id)
+        else {
+            return Err(format!("Imported starboard {id} could not be found").into());
+        };
+
+        summary.starboards += 1;
+
+        for entry in exported.entries {
+            import_starboard_entry(ctx, data, &starboard, &sources, entry, &mut summary).await?;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Adds the emoji tiers and filters of an exported starboard, leaving out channels and roles the guild does not have
+async fn import_starboard_settings(
+    data: &Data,
+    guild_id: GuildId,
+    id: i32,
+    exported: &ExportedStarboard,
+    channels: &HashMap<ChannelId, impl Sized>,
+    roles: &HashMap<RoleId, impl Sized>,
+) -> Result<(), SQLxError> {
+    for tier in &exported.emoji_tiers {
+        add_starboard_emoji_tier(data, guild_id, id, tier.min_count, &tier.emoji).await?;
+    }
+
+    let channel_filters = exported
+        .allowed_channels
+        .iter()
+        .map(|channel| (channel, true))
+        .chain(
+            exported
+                .denied_channels
+                .iter()
+                .map(|channel| (channel, false)),
+        );
+
+    for (channel, allow) in channel_filters {
+        if channels.contains_key(channel) {
+            add_starboard_channel_filter(data, guild_id, id, *channel, allow).await?;
+        }
+    }
+
+    for role in &exported.required_roles {
+        if roles.contains_key(role) {
+            add_starboard_role_filter(data, guild_id, id, *role).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn import_starboard_entry(
+    ctx: &Context,
+    data: &Data,
+    starboard: &Starboard,
+    sources: &HashSet<ChannelId>,
+    mut entry: ExportedEntry,
+    summary: &mut ImportSummary,
+) -> Result<(), Error> {
+    // A channel outside the guild is never read, the post can at most be kept as history
+    if entry
+        .source_channel
+        .is_some_and(|channel| !sources.contains(&channel))
+    {
+        entry.source_channel = None;
+        entry.source_deleted = true;
+    }
+
+    let already_tracked = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM starboard_tracked WHERE starboard_tracked.message_id = $1 AND starboard_tracked.starboard_id = $2) as "tracked!""#,
+        entry.message_id.into_db(),
+        starboard.id
+    )
+    .fetch_one(&data.db)
+    .await?;
+
+    if already_tracked {
+        summary.relinked += 1;
+        return Ok(());
+    }
+
+    if is_import_declined(data, starboard, &entry).await? {
+        summary.declined += 1;
+        return Ok(());
+    }
+
+    let channel = starboard.starboard_channel.into_serenity();
+
+    if let Some(post) = entry.starboard_post_id {
+        if fetch_post(ctx, channel, post).await.is_ok() {
+            track_imported_entry(data, starboard, &entry, post, entry.source_deleted).await?;
+            summary.relinked += 1;
+            return Ok(());
+        }
+    }
+
+    if let Some(source_channel) = entry.source_channel.filter(|_| !entry.source_deleted) {
+        if let Ok(message) = source_channel.message(ctx, entry.message_id).await {
+            // Recounted like any other message, so the policies and filters of the starboard apply
+            let guild_id = starboard.guild_id.into_serenity();
+            let tiers = get_starboard_tiers(data, guild_id, &starboard.emoji).await?;
+            let emoji = ReactionType::try_from(starboard.emoji.as_str())?;
+
+            if update_starboard_entry(ctx, data, message, emoji, &tiers).await? {
+                summary.reposted += 1;
+            } else {
+                summary.declined += 1;
+            }
+
+            return Ok(());
+        }
+    }
+
+    summary.lost += 1;
+
+    Ok(())
+}
+
+/// Whether the message of an entry is locked or removed, or its author blacklisted, which keeps it from being posted again
+async fn is_import_declined(
+    data: &Data,
+    starboard: &Starboard,
+    entry: &ExportedEntry,
+) -> Result<bool, SQLxError> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM starboard_locked_message WHERE starboard_locked_message.guild_id = $1 AND starboard_locked_message.message_id = $2)
+            OR EXISTS (SELECT 1 FROM starboard_blacklist WHERE starboard_blacklist.guild_id = $1 AND starboard_blacklist.user_id = $3) as "declined!""#,
+        starboard.guild_id.into_serenity().into_db(),
+        entry.message_id.into_db(),
+        entry.author_id.map(IntoDatabase::into_db)
+    )
+    .fetch_one(&data.db)
+    .await
+}
+
+async fn track_imported_entry(
+    data: &Data,
+    starboard: &Starboard,
+    entry: &ExportedEntry,
+    post: MessageId,
+    source_deleted: bool,
+) -> Result<(), SQLxError> {
+    sqlx::query!(
+        r#"INSERT INTO starboard_tracked (message_id, starboard_id, starboard_post_id, reaction_count, source_channel, author_id, source_deleted)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+        entry.message_id.into_db(),
+        starboard.id,
+        post.into_db(),
+        entry.reaction_count,
+        entry.source_channel.map(IntoDatabase::into_db),
+        entry.author_id.map(IntoDatabase::into_db),
+        source_deleted
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_own_export() {
+        let export = StarboardExport {
+            version: EXPORT_VERSION,
+            starboards: Vec::new(),
+        };
+        let file = serde_json::to_vec(&export).unwrap();
+
+        let import = parse_starboard_import(&file).unwrap();
+
+        assert_eq!(import.version, EXPORT_VERSION);
+        assert!(import.starboards.is_empty());
+    }
+
+    #[test]
+    fn rejects_newer_export_versions() {
+        let file = format!(r#"{{"version": {}, "starboards": []}}"#, EXPORT_VERSION + 1);
+
+        assert!(parse_starboard_import(file.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn reads_foreign_board_with_camel_case_string_ids() {
+        let file = r#"{
+            "starEmoji": "⭐",
+            "requiredStars": 5,
+            "channelId": "123456789012345678",
+            "messages": [
+                {
+                    "messageId": "223456789012345678",
+                    "channelId": "323456789012345678",
+                    "starboardMessageId": "423456789012345678",
+                    "authorId": "523456789012345678",
+                    "stars": 7
+                }
+            ]
+        }"#;
+
+        let import = parse_starboard_import(file.as_bytes()).unwrap();
+        let board = &import.starboards[0];
+
+        assert_eq!(board.emoji, "⭐");
+        assert_eq!(board.min_reactions, 5);
+        assert_eq!(
+            board.starboard_channel,
+            Some(ChannelId::new(123456789012345678))
+        );
+
+        let entry = &board.entries[0];
+
+        assert_eq!(entry.message_id, MessageId::new(223456789012345678));
+        assert_eq!(
+            entry.source_channel,
+            Some(ChannelId::new(323456789012345678))
+        );
+        assert_eq!(
+            entry.starboard_post_id,
+            Some(MessageId::new(423456789012345678))
+        );
+        assert_eq!(entry.author_id, Some(UserId::new(523456789012345678)));
+        assert_eq!(entry.reaction_count, 7);
+        assert!(!entry.source_deleted);
+    }
+
+    #[test]
+    fn reads_foreign_board_with_snake_case_numeric_ids() {
+        let file = br#"{
+            "threshold": 3,
+            "starboard_channel_id": 123456789012345678,
+            "starred": [
+                {"original_message_id": 223456789012345678, "original_channel_id": 323456789012345678, "star_count": 4}
+            ]
+        }"#;
+
+        let import = parse_starboard_import(file).unwrap();
+        let board = &import.starboards[0];
+
+        assert_eq!(board.emoji, DEFAULT_IMPORT_EMOJI);
+        assert_eq!(board.min_reactions, 3);
+        assert_eq!(board.entries[0].reaction_count, 4);
+        assert_eq!(board.entries[0].starboard_post_id, None);
+    }
+
+    #[test]
+    fn reads_bare_list_of_posts() {
+        let file = br#"[
+            {"id": "223456789012345678", "count": 6},
+            {"id": "323456789012345678", "count": 2}
+        ]"#;
+
+        let import = parse_starboard_import(file).unwrap();
+        let board = &import.starboards[0];
+
+        assert_eq!(board.starboard_channel, None);
+        assert_eq!(board.entries.len(), 2);
+        // Without a threshold the lowest count keeps every post on the board
+        assert_eq!(board.min_reactions, 2);
+    }
+
+    #[test]
+    fn rejects_unknown_files() {
+        assert!(parse_starboard_import(b"not json").is_none());
+        assert!(parse_starboard_import(br#"{"something": "else"}"#).is_none());
+    }
+
+    #[test]
+    fn validates_against_command_limits() {
+        let file = br#"[{"id": "223456789012345678", "count": 6}]"#;
+        let mut import = parse_starboard_import(file).unwrap();
+
+        assert!(check_import_limits(&import).is_ok());
+
+        import.starboards[0].min_reactions = 0;
+        assert!(check_import_limits(&import).is_err());
+
+        import.starboards[0].min_reactions = 1;
+        import.starboards[0].content_template = "a".repeat(MAX_TEMPLATE_LENGTH + 1);
+        assert!(check_import_limits(&import).is_err());
+
+        import.starboards[0].content_template = DEFAULT_CONTENT_TEMPLATE.to_string();
+        import.starboards[0].min_account_days = -1;
+        assert!(check_import_limits(&import).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use core::time::Duration;
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::database::{self, IntoDatabase};
+use crate::{Data, Error};
+use poise::serenity_prelude::{ChannelId, Context, GuildId, Message, RoleId};
+use regex::{Regex, RegexBuilder};
+use sqlx::Error as SQLxError;
+
+/// The most memory a compiled pattern can take up, so no trigger slows down every message
+const PATTERN_SIZE_LIMIT: usize = 256 * 1024;
+
+/// How a trigger's pattern is matched against messages
+#[derive(sqlx::Type, poise::ChoiceParameter, Clone, Copy, Debug)]
+#[sqlx(type_name = "tag_trigger_kind", rename_all = "lowercase")]
+pub enum TriggerKind {
+    /// The pattern appears in the message as a whole word or phrase, ignoring case
+    Keyword,
+    /// The whole message matches the pattern, where `*` is any text and `?` any one character
+    Glob,
+    /// The message matches the pattern as a regular expression
+    Regex,
+}
+
+/// A trigger that is ready to be matched against messages
+struct CompiledTrigger {
+    id: i32,
+    command_name: String,
+    pattern: Regex,
+    cooldown: Duration,
+}
+
+/// Everything needed to check a guild's messages against its triggers
+pub struct GuildTriggers {
+    channels: HashSet<ChannelId>,
+    ignored_roles: HashSet<RoleId>,
+    triggers: Vec<CompiledTrigger>,
+}
+
+/// Turns a trigger's pattern into a regex, erroring if it is not a valid one
+pub fn compile_trigger(kind: TriggerKind, pattern: &str) -> Result<Regex, regex::Error> {
+    let pattern = match kind {
+        // Word boundaries do not work next to symbols, so look for anything that is not part of a word
+        TriggerKind::Keyword => format!(r"(?i)(?:^|\W){}(?:\W|$)", regex::escape(pattern.trim())),
+        TriggerKind::Glob => {
+            let mut regex = String::from(r"(?is)^\s*");
+
+            for c in pattern.trim().chars() {
+                match c {
+                    '*' => regex.push_str(".*"),
+                    '?' => regex.push('.'),
+                    c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+                }
+            }
+
+            regex.push_str(r"\s*$");
+            regex
+        }
+        TriggerKind::Regex => pattern.to_string(),
+    };
+
+    RegexBuilder::new(&pattern)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .build()
+}
+
+async fn load_guild_triggers(data: &Data, guild: GuildId) -> Result<GuildTriggers, SQLxError> {
+    let channels = sqlx::query_scalar!(
+        r#"SELECT channel_id as "channel_id: database::ChannelId" FROM tag_trigger_channel WHERE guild_id = $1"#,
+        guild.into_db()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let ignored_roles = sqlx::query_scalar!(
+        r#"SELECT role_id as "role_id: database::RoleId" FROM tag_trigger_ignored_role WHERE guild_id = $1"#,
+        guild.into_db()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let rows = sqlx::query!(
+        r#"SELECT id, command_name, kind as "kind: TriggerKind", pattern, cooldown_seconds
+        FROM tag_trigger WHERE guild_id = $1 ORDER BY id"#,
+        guild.into_db()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let mut triggers = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        // Patterns are checked when they are added, so this only happens if the limits change
+        match compile_trigger(row.kind, &row.pattern) {
+            Ok(pattern) => triggers.push(CompiledTrigger {
+                id: row.id,
+                command_name: row.command_name,
+                pattern,
+                cooldown: Duration::from_secs(row.cooldown_seconds.max(0) as u64),
+            }),
+            Err(err) => tracing::warn!("Skipping tag trigger {}: {err}", row.id),
+        }
+    }
+
+    Ok(GuildTriggers {
+        channels: channels.into_iter().map(|id| id.into_serenity()).collect(),
+        ignored_roles: ignored_roles
+            .into_iter()
+            .map(|id| id.into_serenity())
+            .collect(),
+        triggers,
+    })
+}
+
+/// The triggers of a guild, which are loaded from the database the first time they are needed
+async fn get_guild_triggers(data: &Data, guild: GuildId) -> Result<Arc<GuildTriggers>, SQLxError> {
+    if let Some(triggers) = data.tag_triggers.get(&guild) {
+        return Ok(Arc::clone(&triggers));
+    }
+
+    let generation = data.tag_trigger_generation.load(Ordering::Acquire);
+    let triggers = Arc::new(load_guild_triggers(data, guild).await?);
+
+    // Holding the entry keeps an invalidation from removing it until it is either cached or known to be stale
+    let entry = data.tag_triggers.entry(guild);
+
+    if data.tag_trigger_generation.load(Ordering::Acquire) == generation {
+        entry.or_insert_with(|| Arc::clone(&triggers));
+    }
+
+    Ok(triggers)
+}
+
+/// Forgets the cached triggers of a guild, so changes to them are picked up by the next message.
+/// Cooldowns that have run out are dropped as well, so removed triggers do not keep theirs forever.
+pub fn invalidate_tag_triggers(data: &Data, guild: GuildId) {
+    data.tag_trigger_generation.fetch_add(1, Ordering::AcqRel);
+    data.tag_triggers.remove(&guild);
+
+    let now = Instant::now();
+    data.tag_trigger_cooldowns.retain(|_, until| *until > now);
+}
+
+/// The tag a message should be answered with, if it matches a trigger that is not cooling down.
+/// Messages from bots, from members with an ignored role or outside the enabled channels never match.
+pub async fn find_triggered_tag(
+    ctx: &Context,
+    data: &Data,
+    message: &Message,
+) -> Result<Option<String>, Error> {
+    let Some(guild) = message.guild_id else {
+        return Ok(None);
+    };
+
+    if message.author.bot || message.webhook_id.is_some() || message.content.is_empty() {
+        return Ok(None);
+    }
+
+    let triggers = get_guild_triggers(data, guild).await?;
+
+    if triggers.triggers.is_empty() || triggers.channels.is_empty() {
+        return Ok(None);
+    }
+
+    let ignored = message.member.as_ref().is_some_and(|member| {
+        member
+            .roles
+            .iter()
+            .any(|role| triggers.ignored_roles.contains(role))
+    });
+
+    if ignored {
+        return Ok(None);
+    }
+
+    if !triggers.channels.contains(&message.channel_id)
+        && !has_enabled_parent(ctx, &triggers.channels, message.channel_id).await?
+    {
+        return Ok(None);
+    }
+
+    let now = Instant::now();
+
+    // A trigger that is cooling down leaves the message to the next one that matches
+    let triggered = triggers
+        .triggers
+        .iter()
+        .filter(|trigger| trigger.pattern.is_match(&message.content))
+        .find(|trigger| claim_trigger(data, trigger, now));
+
+    Ok(triggered.map(|trigger| trigger.command_name.clone()))
+}
+
+/// Whether a channel is enabled through its category, or a thread enabled through its parent channel or that channel's category
+async fn has_enabled_parent(
+    ctx: &Context,
+    channels: &HashSet<ChannelId>,
+    channel: ChannelId,
+) -> Result<bool, Error> {
+    let Some(channel) = channel.to_channel(ctx).await?.guild() else {
+        return Ok(false);
+    };
+
+    let Some(parent) = channel.parent_id else {
+        return Ok(false);
+    };
+
+    if channels.contains(&parent) {
+        return Ok(true);
+    }
+
+    if channel.thread_metadata.is_none() {
+        return Ok(false);
+    }
+
+    let Some(parent) = parent.to_channel(ctx).await?.guild() else {
+        return Ok(false);
+    };
+
+    Ok(parent
+        .parent_id
+        .is_some_and(|category| channels.contains(&category)))
+}
+
+/// Starts the cooldown of a trigger unless it is already cooling down, returning whether it may reply.
+/// Claimed before the tag is sent, so a burst of matching messages only gets one reply.
+fn claim_trigger(data: &Data, trigger: &CompiledTrigger, now: Instant) -> bool {
+    let mut claimed = true;
+
+    data.tag_trigger_cooldowns
+        .entry(trigger.id)
+        .and_modify(|until| {
+            if now < *until {
+                claimed = false;
+            } else {
+                *until = now + trigger.cooldown;
+            }
+        })
+        .or_insert(now + trigger.cooldown);
+
+    claimed
+}
+
+/// Stops triggers from replying in a channel, for when it is deleted
+pub async fn remove_trigger_channel(
+    data: &Data,
+    guild: GuildId,
+    channel: ChannelId,
+) -> Result<(), SQLxError> {
+    let removed = sqlx::query!(
+        "DELETE FROM tag_trigger_channel WHERE channel_id = $1",
+        channel.into_db()
+    )
+    .execute(&data.db)
+    .await?;
+
+    if removed.rows_affected() > 0 {
+        invalidate_tag_triggers(data, guild);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyword_matches_next_to_punctuation() {
+        let pattern = compile_trigger(TriggerKind::Keyword, "c++").unwrap();
+
+        assert!(pattern.is_match("c++"));
+        assert!(pattern.is_match("How do I learn C++?"));
+        assert!(pattern.is_match("(c++)"));
+        assert!(!pattern.is_match("c++11"));
+        assert!(!pattern.is_match("abc++"));
+    }
+
+    #[test]
+    fn keyword_matches_whole_words_only() {
+        let pattern = compile_trigger(TriggerKind::Keyword, "rust").unwrap();
+
+        assert!(pattern.is_match("I like Rust."));
+        assert!(pattern.is_match("rust, go"));
+        assert!(!pattern.is_match("trusty"));
+    }
+
+    #[test]
+    fn glob_escapes_regex_syntax() {
+        let pattern = compile_trigger(TriggerKind::Glob, "v1.0 (beta)").unwrap();
+
+        assert!(pattern.is_match("v1.0 (beta)"));
+        assert!(!pattern.is_match("v1x0 (beta)"));
+        assert!(!pattern.is_match("v1.0 beta"));
+    }
+
+    #[test]
+    fn glob_wildcards_match_the_whole_message() {
+        let pattern = compile_trigger(TriggerKind::Glob, "how do i * in ?").unwrap();
+
+        assert!(pattern.is_match("How do I install it in C"));
+        assert!(pattern.is_match("  how do i get\nhelp in C  "));
+        assert!(!pattern.is_match("how do i install it in CC"));
+        assert!(!pattern.is_match("so how do i install it in C"));
+    }
+
+    #[test]
+    fn regex_over_the_size_limit_is_rejected() {
+        assert!(compile_trigger(TriggerKind::Regex, r"\w{1000}\w{1000}\w{1000}").is_err());
+        assert!(compile_trigger(TriggerKind::Regex, r"\b\w+\b").is_ok());
+    }
+
+    #[test]
+    fn invalid_regex_is_rejected() {
+        assert!(compile_trigger(TriggerKind::Regex, "(unclosed").is_err());
+    }
+}
@@ -4,9 +4,13 @@ use serenity::{Context, FullEvent};
 mod channel_delete;
 mod guild_member_addition;
 mod guild_member_removal;
+mod message;
 mod message_delete;
+mod message_delete_bulk;
+mod message_update;
 mod reaction_add;
 mod reaction_remove;
+mod thread_delete;
 
 pub async fn event_handler(ctx: &Context, event: &FullEvent, data: &Data) -> Result<(), Error> {
     match event {
@@ -16,11 +20,23 @@ pub async fn event_handler(ctx: &Context, event: &FullEvent, data: &Data) -> Res
         FullEvent::ReactionRemove { removed_reaction } => {
             reaction_remove::handle(removed_reaction, data, ctx).await?
         }
+        FullEvent::Message { new_message } => {
+            message::handle(new_message, data, ctx).await?;
+        }
         FullEvent::MessageDelete {
             deleted_message_id, ..
         } => {
             message_delete::handle(deleted_message_id, data, ctx).await?;
         }
+        FullEvent::MessageDeleteBulk {
+            multiple_deleted_messages_ids,
+            ..
+        } => {
+            message_delete_bulk::handle(multiple_deleted_messages_ids, data, ctx).await?;
+        }
+        FullEvent::MessageUpdate { event, .. } => {
+            message_update::handle(event, data, ctx).await?;
+        }
         FullEvent::GuildMemberAddition { new_member } => {
             guild_member_addition::handle(new_member, data, ctx).await?;
         }
@@ -28,7 +44,10 @@ pub async fn event_handler(ctx: &Context, event: &FullEvent, data: &Data) -> Res
             guild_member_removal::handle(guild_id, user, data, ctx).await?;
         }
         FullEvent::ChannelDelete { channel, .. } => {
-            channel_delete::handle(channel, data).await?;
+            channel_delete::handle(channel, data, ctx).await?;
+        }
+        FullEvent::ThreadDelete { thread, .. } => {
+            thread_delete::handle(thread, data, ctx).await?;
         }
         _ => (),
     }
@@ -1,8 +1,20 @@
-use crate::{database::starboard::delete_starboard_tables, serenity, Data, Error};
-use serenity::GuildChannel;
+use crate::{
+    database::starboard::{delete_starboard_tables, handle_deleted_source_channel},
+    database::tag_triggers::remove_trigger_channel,
+    serenity, Data, Error,
+};
+use serenity::{Context, GuildChannel};
 
-pub async fn handle(deleted_channel: &GuildChannel, data: &Data) -> Result<(), Error> {
+pub async fn handle(
+    deleted_channel: &GuildChannel,
+    data: &Data,
+    ctx: &Context,
+) -> Result<(), Error> {
     delete_starboard_tables(data, deleted_channel.id).await?;
 
+    handle_deleted_source_channel(ctx, data, deleted_channel.id).await?;
+
+    remove_trigger_channel(data, deleted_channel.guild_id, deleted_channel.id).await?;
+
     Ok(())
 }
@@ -0,0 +1,13 @@
+use crate::{
+    commands::tags::send_triggered_tag, database::tag_triggers::find_triggered_tag, serenity, Data,
+    Error,
+};
+use serenity::{Context, Message};
+
+pub async fn handle(message: &Message, data: &Data, ctx: &Context) -> Result<(), Error> {
+    if let Some(name) = find_triggered_tag(ctx, data, message).await? {
+        send_triggered_tag(ctx, data, message, &name).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use crate::{database::starboard::handle_deleted_messages, serenity, Data, Error};
+use serenity::{Context, MessageId};
+
+pub async fn handle(
+    deleted_messages: &[MessageId],
+    data: &Data,
+    ctx: &Context,
+) -> Result<(), Error> {
+    handle_deleted_messages(ctx, data, deleted_messages).await?;
+
+    Ok(())
+}
@@ -0,0 +1,8 @@
+use crate::{database::starboard::refresh_starboard_posts, serenity, Data, Error};
+use serenity::{Context, MessageUpdateEvent};
+
+pub async fn handle(event: &MessageUpdateEvent, data: &Data, ctx: &Context) -> Result<(), Error> {
+    refresh_starboard_posts(ctx, data, event.channel_id, event.id).await?;
+
+    Ok(())
+}
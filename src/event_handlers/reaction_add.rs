@@ -1,8 +1,8 @@
-use crate::{database::starboard::manage_starboard_entry, serenity, Data, Error};
+use crate::{database::starboard::queue_starboard_update, serenity, Data, Error};
 use serenity::{Context, Reaction};
 
 pub async fn handle(reaction: &Reaction, data: &Data, ctx: &Context) -> Result<(), Error> {
-    manage_starboard_entry(ctx, data, reaction).await?;
+    queue_starboard_update(ctx, data, reaction);
 
     Ok(())
 }
@@ -0,0 +1,12 @@
+use crate::{database::starboard::handle_deleted_source_channel, serenity, Data, Error};
+use serenity::{Context, PartialGuildChannel};
+
+pub async fn handle(
+    deleted_thread: &PartialGuildChannel,
+    data: &Data,
+    ctx: &Context,
+) -> Result<(), Error> {
+    handle_deleted_source_channel(ctx, data, deleted_thread.id).await?;
+
+    Ok(())
+}
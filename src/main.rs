@@ -11,6 +11,7 @@ use dotenvy::dotenv;
 use poise::serenity_prelude::ActivityData;
 use poise::serenity_prelude::Client;
 
+use crate::database::starboard::reconcile_starboards;
 use crate::event_handlers::event_handler;
 use poise::serenity_prelude as serenity;
 use poise::Prefix;
@@ -19,6 +20,7 @@ use serenity::{Color, GatewayIntents};
 mod commands;
 mod database;
 mod event_handlers;
+mod util;
 
 const EMBED_COLOR: Color = Color::from_rgb(255, 172, 51);
 
@@ -41,10 +43,11 @@ async fn main() {
         goodbye(),
         tag_edit(),
         tag_list(),
+        tag_search(),
         tags(),
         burg_vote(),
         silly_check(),
-        count_reactions()
+        count_reactions(),
     ];
 
     match dotenv() {
@@ -86,6 +89,14 @@ async fn main() {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
 
+                // Catch up on any starboard changes we missed while offline
+                let (ctx, reconcile_data) = (ctx.clone(), data.clone());
+                tokio::spawn(async move {
+                    if let Err(err) = reconcile_starboards(&ctx, &reconcile_data).await {
+                        tracing::error!("Failed to reconcile starboards: {err}");
+                    }
+                });
+
                 Ok(data)
             })
         })
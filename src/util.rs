@@ -0,0 +1,34 @@
+/// Discord does not allow message content longer than this
+pub const MAX_CONTENT_LENGTH: usize = 2000;
+
+/// Discord allows at most this many characters in an embed title
+pub const EMBED_TITLE_LENGTH: usize = 256;
+
+/// Discord allows at most this many characters in an embed description
+pub const EMBED_DESCRIPTION_LENGTH: usize = 4096;
+
+/// Discord allows at most this many characters in an embed field value
+pub const EMBED_FIELD_LENGTH: usize = 1024;
+
+/// Discord allows at most this many characters in an embed author name
+pub const EMBED_AUTHOR_LENGTH: usize = 256;
+
+/// Discord allows at most this many characters across all embeds of a message
+pub const TOTAL_EMBED_LENGTH: usize = 6000;
+
+/// Discord allows at most this many characters in the name of an autocomplete choice
+pub const CHOICE_NAME_LENGTH: usize = 100;
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Cuts `text` down to at most `max_chars` characters, ending with an ellipsis if anything was cut
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some(_) if max_chars == 0 => String::new(),
+        Some(_) => {
+            let (end, _) = text.char_indices().nth(max_chars - 1).unwrap();
+            format!("{}…", &text[..end])
+        }
+        None => text.to_string(),
+    }
+}